@@ -4,3 +4,4 @@ set -eo pipefail
 
 ASSETS_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
 cargo run --release --bin refmt-generate-assets -- --assets-dir "$ASSETS_DIR"
+cargo run --release --bin refmt-generate-assets -- --assets-dir "$ASSETS_DIR" --verify
@@ -0,0 +1,23 @@
+fn main() {
+    // the checked-in header is regenerated only on request, so that builds don't write into the
+    // source directory.
+    #[cfg(feature = "header")]
+    {
+        use std::env;
+        use std::path::PathBuf;
+
+        let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+        cbindgen::Builder::new()
+            .with_crate(&crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("Unable to generate the header")
+            .write_to_file(crate_dir.join("include").join("refmt.h"));
+
+        println!("cargo:rerun-if-changed=src/lib.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+    }
+    #[cfg(not(feature = "header"))]
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,114 @@
+/* The C API of refmt. Generated by cbindgen from refmt-ffi, do not edit. */
+
+#ifndef REFMT_H
+#define REFMT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of the functions. The values are stable, and new ones are only appended.
+ */
+typedef enum RefmtStatus {
+  REFMT_STATUS_OK = 0,
+  /**
+   * A pointer is null, or a format name is not UTF-8.
+   */
+  REFMT_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * The format name is unknown.
+   */
+  REFMT_STATUS_FORMAT_NAME = 2,
+  /**
+   * The input format can only be written.
+   */
+  REFMT_STATUS_WRITE_ONLY_FORMAT = 3,
+  REFMT_STATUS_IO = 4,
+  /**
+   * The output of a text format is not UTF-8.
+   */
+  REFMT_STATUS_UTF8 = 5,
+  REFMT_STATUS_BSON = 10,
+  REFMT_STATUS_EDN = 11,
+  REFMT_STATUS_HCL = 12,
+  REFMT_STATUS_JSON = 13,
+  REFMT_STATUS_JSON5 = 14,
+  REFMT_STATUS_KDL = 15,
+  REFMT_STATUS_PLIST = 16,
+  REFMT_STATUS_RON = 17,
+  REFMT_STATUS_TABLE = 18,
+  REFMT_STATUS_TOML = 19,
+  REFMT_STATUS_YAML = 20,
+  /**
+   * The conversion panicked, which is a bug of refmt.
+   */
+  REFMT_STATUS_PANIC = 98,
+  REFMT_STATUS_OTHER = 99,
+} RefmtStatus;
+
+/**
+ * An error of the conversion, which is freed by `refmt_error_free`.
+ */
+typedef struct RefmtError RefmtError;
+
+/**
+ * Converts `src` from the format `from` into the format `to`, e.g. `"json"` and `"yaml"`.
+ * The names and the extensions of the command line tool are accepted.
+ *
+ * On success, `*out` and `*out_len` are set to the output, which is freed by `refmt_free`.
+ * Otherwise, `*err` is set to the error if `err` is not null, which is freed by
+ * `refmt_error_free`.
+ *
+ * # Safety
+ *
+ * `src` must point to `src_len` bytes, or may be null if `src_len` is 0. `from` and `to` must
+ * be NUL terminated strings. `out` and `out_len` must be valid pointers to write into.
+ */
+enum RefmtStatus refmt_convert(const uint8_t *src,
+                               size_t src_len,
+                               const char *from,
+                               const char *to,
+                               uint8_t **out,
+                               size_t *out_len,
+                               struct RefmtError **err);
+
+/**
+ * Frees the output of `refmt_convert`.
+ *
+ * # Safety
+ *
+ * `out` and `out_len` must be the output of `refmt_convert`, which is not freed yet.
+ */
+void refmt_free(uint8_t *out, size_t out_len);
+
+/**
+ * Returns the status of the error.
+ *
+ * # Safety
+ *
+ * `err` must be an error of `refmt_convert`, which is not freed yet.
+ */
+enum RefmtStatus refmt_error_status(const struct RefmtError *err);
+
+/**
+ * Returns the message of the error, which lives until the error is freed.
+ *
+ * # Safety
+ *
+ * `err` must be an error of `refmt_convert`, which is not freed yet.
+ */
+const char *refmt_error_message(const struct RefmtError *err);
+
+/**
+ * Frees the error of `refmt_convert`.
+ *
+ * # Safety
+ *
+ * `err` must be an error of `refmt_convert`, which is not freed yet.
+ */
+void refmt_error_free(struct RefmtError *err);
+
+#endif /* REFMT_H */
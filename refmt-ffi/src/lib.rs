@@ -0,0 +1,336 @@
+//! The C API of refmt, for the programs which link the same conversion as the command line tool.
+//!
+//! The header `include/refmt.h` is checked in, and regenerated by cbindgen on build with the
+//! `header` feature.
+//!
+//! ```c
+//! uint8_t *out;
+//! size_t out_len;
+//! RefmtError *err;
+//! if (refmt_convert(src, src_len, "json", "yaml", &out, &out_len, &err) == REFMT_STATUS_OK) {
+//!     fwrite(out, 1, out_len, stdout);
+//!     refmt_free(out, out_len);
+//! } else {
+//!     fprintf(stderr, "%s\n", refmt_error_message(err));
+//!     refmt_error_free(err);
+//! }
+//! ```
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::str::FromStr;
+
+use refmt::converter::Converter;
+use refmt::errors;
+use refmt::format::FileFormat;
+use refmt_serde::RefmtError;
+
+/// The result of the functions. The values are stable, and new ones are only appended.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    Ok = 0,
+    /// A pointer is null, or a format name is not UTF-8.
+    InvalidArgument = 1,
+    /// The format name is unknown.
+    FormatName = 2,
+    /// The input format can only be written.
+    WriteOnlyFormat = 3,
+    Io = 4,
+    /// The output of a text format is not UTF-8.
+    Utf8 = 5,
+    Bson = 10,
+    Edn = 11,
+    Hcl = 12,
+    Json = 13,
+    Json5 = 14,
+    Kdl = 15,
+    Plist = 16,
+    Ron = 17,
+    Table = 18,
+    Toml = 19,
+    Yaml = 20,
+    /// The conversion panicked, which is a bug of refmt.
+    Panic = 98,
+    Other = 99,
+}
+
+impl From<&errors::Error> for Status {
+    fn from(e: &errors::Error) -> Self {
+        match e {
+            errors::Error::FormatName(_) => Status::FormatName,
+            errors::Error::WriteOnlyFormat(_) => Status::WriteOnlyFormat,
+            errors::Error::Io(_) => Status::Io,
+            errors::Error::RefmtError(e) => match e {
+                RefmtError::Bson(_) => Status::Bson,
+                RefmtError::Edn(_) => Status::Edn,
+                RefmtError::Hcl(_) => Status::Hcl,
+                RefmtError::Json(_) => Status::Json,
+                RefmtError::Json5(_) => Status::Json5,
+                RefmtError::Kdl(_) => Status::Kdl,
+                RefmtError::Plist(_) => Status::Plist,
+                RefmtError::Ron(_) => Status::Ron,
+                RefmtError::Table(_) => Status::Table,
+                RefmtError::Toml(_) => Status::Toml,
+                RefmtError::Yaml(_) => Status::Yaml,
+                RefmtError::Utf8(_) => Status::Utf8,
+                RefmtError::Io(_) => Status::Io,
+                // the transforms are not available in the C API.
+                RefmtError::Interpolation(_) | RefmtError::Flatten(_) => Status::Other,
+            },
+            _ => Status::Other,
+        }
+    }
+}
+
+/// An error of the conversion, which is freed by `refmt_error_free`.
+pub struct Error {
+    status: Status,
+    message: CString,
+}
+
+impl Error {
+    fn new(status: Status, message: String) -> Error {
+        // a message can't have NUL, which ends the C string.
+        let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+        Error { status, message }
+    }
+}
+
+impl From<errors::Error> for Error {
+    fn from(e: errors::Error) -> Self {
+        let message = match &e {
+            errors::Error::RefmtError(inner) => match std::error::Error::source(inner) {
+                Some(cause) => format!("{} cause:{}", e, cause),
+                None => e.to_string(),
+            },
+            _ => e.to_string(),
+        };
+        Error::new(Status::from(&e), message)
+    }
+}
+
+/// Converts `src` from the format `from` into the format `to`, e.g. `"json"` and `"yaml"`.
+/// The names and the extensions of the command line tool are accepted.
+///
+/// On success, `*out` and `*out_len` are set to the output, which is freed by `refmt_free`.
+/// Otherwise, `*err` is set to the error if `err` is not null, which is freed by
+/// `refmt_error_free`.
+///
+/// # Safety
+///
+/// `src` must point to `src_len` bytes, or may be null if `src_len` is 0. `from` and `to` must
+/// be NUL terminated strings. `out` and `out_len` must be valid pointers to write into.
+#[no_mangle]
+pub unsafe extern "C" fn refmt_convert(
+    src: *const u8,
+    src_len: usize,
+    from: *const c_char,
+    to: *const c_char,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+    err: *mut *mut Error,
+) -> Status {
+    if !err.is_null() {
+        *err = ptr::null_mut();
+    }
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        convert(src, src_len, from, to, out, out_len)
+    }))
+    .unwrap_or_else(|_| {
+        Err(Error::new(
+            Status::Panic,
+            "refmt panicked on the conversion".to_string(),
+        ))
+    });
+
+    match r {
+        Ok(()) => Status::Ok,
+        Err(e) => {
+            let status = e.status;
+            if !err.is_null() {
+                *err = Box::into_raw(Box::new(e));
+            }
+            status
+        }
+    }
+}
+
+unsafe fn convert(
+    src: *const u8,
+    src_len: usize,
+    from: *const c_char,
+    to: *const c_char,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> Result<(), Error> {
+    if (src.is_null() && src_len > 0) || out.is_null() || out_len.is_null() {
+        return Err(Error::new(
+            Status::InvalidArgument,
+            "src, out and out_len must not be null".to_string(),
+        ));
+    }
+    let src = if src_len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(src, src_len)
+    };
+    let from = format(from)?;
+    let to = format(to)?;
+
+    let mut bytes = Vec::new();
+    Converter::new()
+        .from(from)
+        .to(to)
+        .convert(src, &mut bytes)?;
+
+    let bytes = bytes.into_boxed_slice();
+    *out_len = bytes.len();
+    *out = Box::into_raw(bytes) as *mut u8;
+    Ok(())
+}
+
+unsafe fn format(name: *const c_char) -> Result<FileFormat, Error> {
+    if name.is_null() {
+        return Err(Error::new(
+            Status::InvalidArgument,
+            "the format name must not be null".to_string(),
+        ));
+    }
+    let name = CStr::from_ptr(name).to_str().map_err(|_| {
+        Error::new(
+            Status::InvalidArgument,
+            "the format name must be UTF-8".to_string(),
+        )
+    })?;
+    Ok(FileFormat::from_str(name)?)
+}
+
+/// Frees the output of `refmt_convert`.
+///
+/// # Safety
+///
+/// `out` and `out_len` must be the output of `refmt_convert`, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn refmt_free(out: *mut u8, out_len: usize) {
+    if !out.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(out, out_len)));
+    }
+}
+
+/// Returns the status of the error.
+///
+/// # Safety
+///
+/// `err` must be an error of `refmt_convert`, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn refmt_error_status(err: *const Error) -> Status {
+    match err.as_ref() {
+        Some(e) => e.status,
+        None => Status::Ok,
+    }
+}
+
+/// Returns the message of the error, which lives until the error is freed.
+///
+/// # Safety
+///
+/// `err` must be an error of `refmt_convert`, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn refmt_error_message(err: *const Error) -> *const c_char {
+    match err.as_ref() {
+        Some(e) => e.message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Frees the error of `refmt_convert`.
+///
+/// # Safety
+///
+/// `err` must be an error of `refmt_convert`, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn refmt_error_free(err: *mut Error) {
+    if !err.is_null() {
+        drop(Box::from_raw(err));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    use super::*;
+
+    /// Converts with the C API, and returns the output or the status and the message.
+    fn convert(src: &[u8], from: &str, to: &str) -> Result<Vec<u8>, (Status, String)> {
+        let from = CString::new(from).unwrap();
+        let to = CString::new(to).unwrap();
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        let mut err = ptr::null_mut();
+        unsafe {
+            let status = refmt_convert(
+                src.as_ptr(),
+                src.len(),
+                from.as_ptr(),
+                to.as_ptr(),
+                &mut out,
+                &mut out_len,
+                &mut err,
+            );
+            if status == Status::Ok {
+                assert!(err.is_null());
+                let bytes = std::slice::from_raw_parts(out, out_len).to_vec();
+                refmt_free(out, out_len);
+                Ok(bytes)
+            } else {
+                assert_eq!(status, refmt_error_status(err));
+                let message = CStr::from_ptr(refmt_error_message(err))
+                    .to_string_lossy()
+                    .into_owned();
+                refmt_error_free(err);
+                Err((status, message))
+            }
+        }
+    }
+
+    #[test]
+    fn convert_formats() {
+        let r = convert(br#"{"a": [1, 2]}"#, "json", "yml");
+        assert_eq!(Ok(b"a:\n- 1\n- 2\n".to_vec()), r);
+
+        let r = convert(b"", "yaml", "json");
+        assert_eq!(Ok(b"null\n".to_vec()), r);
+    }
+
+    #[test]
+    fn errors() {
+        let r = convert(b"{", "json", "yaml");
+        assert!(matches!(r, Err((Status::Json, m)) if m.contains("EOF")));
+
+        let r = convert(b"a = ", "toml", "json");
+        assert!(matches!(r, Err((Status::Toml, _))));
+
+        let r = convert(b"{}", "conf", "json");
+        assert!(matches!(r, Err((Status::FormatName, m)) if m.contains("conf")));
+
+        let r = convert(b"", "markdown-table", "json");
+        assert!(matches!(r, Err((Status::WriteOnlyFormat, _))));
+
+        let status = unsafe {
+            refmt_convert(
+                ptr::null(),
+                1,
+                ptr::null(),
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        assert_eq!(Status::InvalidArgument, status);
+    }
+}
@@ -0,0 +1,290 @@
+//! The `refmt` Python module, which converts documents like the command line tool.
+//!
+//! Values are mapped through JSON values of refmt, so that numbers and dates are read and written
+//! the same way for every format. Dates, i.e. `$date` of refmt and the datetimes of TOML, are
+//! `datetime` objects. Build a wheel with `maturin build`.
+
+// #[pyfunction] of pyo3 0.22 converts the errors of PyResult into PyErr again.
+#![allow(clippy::useless_conversion)]
+
+use std::str::FromStr;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{
+    PyBool, PyBytes, PyDate, PyDateTime, PyDict, PyFloat, PyInt, PyList, PyString, PyTime, PyTuple,
+};
+use refmt::converter::Converter;
+use refmt::errors;
+use refmt::format::FileFormat;
+use serde_json::{Map, Number, Value};
+
+/// The key of the dates of refmt, which formats with dates read and write.
+const DATE_KEY: &str = "$date";
+/// The key which the toml crate reads and writes datetimes with.
+const TOML_DATE_KEY: &str = "$__toml_private_datetime";
+
+create_exception!(
+    refmt,
+    RefmtError,
+    PyValueError,
+    "A document can't be converted."
+);
+
+fn error(e: errors::Error) -> PyErr {
+    RefmtError::new_err(e.to_string())
+}
+
+fn format(name: &str) -> PyResult<FileFormat> {
+    FileFormat::from_str(name).map_err(error)
+}
+
+/// Returns the bytes of `str` or `bytes`.
+fn bytes_of(text: &Bound<'_, PyAny>) -> PyResult<Vec<u8>> {
+    if let Ok(b) = text.downcast::<PyBytes>() {
+        Ok(b.as_bytes().to_vec())
+    } else if let Ok(s) = text.downcast::<PyString>() {
+        Ok(s.to_str()?.as_bytes().to_vec())
+    } else {
+        Err(PyTypeError::new_err("the document must be str or bytes"))
+    }
+}
+
+/// Returns `bytes` for binary formats, and `str` for others.
+fn output(py: Python<'_>, format: FileFormat, bytes: Vec<u8>) -> PyResult<PyObject> {
+    if format.is_binary() {
+        Ok(PyBytes::new_bound(py, &bytes).into_any().unbind())
+    } else {
+        let s = String::from_utf8(bytes).map_err(|e| RefmtError::new_err(e.to_string()))?;
+        Ok(PyString::new_bound(py, &s).into_any().unbind())
+    }
+}
+
+fn convert_bytes(
+    bytes: &[u8],
+    from: FileFormat,
+    to: FileFormat,
+    indent: Option<usize>,
+) -> PyResult<Vec<u8>> {
+    let converter = Converter::new().from(from).to(to);
+    let converter = match indent {
+        Some(indent) => converter.indent(indent),
+        None => converter,
+    };
+    let mut out = Vec::new();
+    converter.convert(bytes, &mut out).map_err(error)?;
+    Ok(out)
+}
+
+/// Reads the document into a JSON value, whose dates are `$date`.
+fn value_of(text: &Bound<'_, PyAny>, format_name: &str) -> PyResult<Value> {
+    let from = format(format_name)?;
+    let json = convert_bytes(&bytes_of(text)?, from, FileFormat::Json, None)?;
+    let mut v = serde_json::from_slice(&json).map_err(|e| RefmtError::new_err(e.to_string()))?;
+    if from == FileFormat::Toml {
+        toml_dates(&mut v);
+    }
+    Ok(v)
+}
+
+/// Renames the key of the datetimes of TOML into `$date`.
+fn toml_dates(v: &mut Value) {
+    match v {
+        Value::Object(m) if m.len() == 1 && m.get(TOML_DATE_KEY).is_some_and(Value::is_string) => {
+            if let Some(date) = m.remove(TOML_DATE_KEY) {
+                m.insert(DATE_KEY.to_string(), date);
+            }
+        }
+        Value::Object(m) => m.values_mut().for_each(toml_dates),
+        Value::Array(a) => a.iter_mut().for_each(toml_dates),
+        _ => {}
+    }
+}
+
+/// Returns the `datetime` of the RFC 3339 text, or the `date` or `time` of a local date or time
+/// of TOML. Returns None if the text isn't any of them.
+fn datetime(py: Python<'_>, s: &str) -> PyResult<Option<PyObject>> {
+    let class = match s.as_bytes() {
+        [_, _, _, _, b'-', _, _, b'-', _, _] => "date",
+        [_, _, b':', ..] => "time",
+        [_, _, _, _, b'-', _, _, b'-', _, _, b'T' | b't' | b' ', ..] => "datetime",
+        _ => return Ok(None),
+    };
+    // `fromisoformat` accepts `Z` only since Python 3.11.
+    let iso = match s.strip_suffix(['Z', 'z']) {
+        Some(s) => format!("{}+00:00", s),
+        None => s.to_string(),
+    };
+    let class = py.import_bound("datetime")?.getattr(class)?;
+    Ok(class
+        .call_method1("fromisoformat", (iso,))
+        .ok()
+        .map(Bound::unbind))
+}
+
+fn to_python(py: Python<'_>, v: &Value) -> PyResult<PyObject> {
+    Ok(match v {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(a) => {
+            let items = a
+                .iter()
+                .map(|v| to_python(py, v))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new_bound(py, items).into_any().unbind()
+        }
+        Value::Object(m) => {
+            if let (1, Some(Value::String(s))) = (m.len(), m.get(DATE_KEY)) {
+                if let Some(d) = datetime(py, s)? {
+                    return Ok(d);
+                }
+            }
+            let dict = PyDict::new_bound(py);
+            for (k, v) in m {
+                dict.set_item(k, to_python(py, v)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    })
+}
+
+/// Maps the object into a JSON value. A `datetime` is a date of the key, i.e. `$date` or the key
+/// of TOML, and other dates and times are written in ISO 8601, or as local ones of TOML.
+fn from_python(obj: &Bound<'_, PyAny>, date_key: &str) -> PyResult<Value> {
+    if obj.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = obj.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if obj.is_instance_of::<PyInt>() {
+        if let Ok(i) = obj.extract::<i64>() {
+            Ok(Value::from(i))
+        } else {
+            Ok(Value::from(obj.extract::<u64>().map_err(|_| {
+                PyValueError::new_err("integers must be in the range of 64 bits")
+            })?))
+        }
+    } else if let Ok(f) = obj.downcast::<PyFloat>() {
+        Number::from_f64(f.value())
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err("NaN and infinity can't be written"))
+    } else if let Ok(s) = obj.downcast::<PyString>() {
+        Ok(Value::String(s.to_str()?.to_string()))
+    } else if let Ok(d) = obj.downcast::<PyDict>() {
+        let mut m = Map::new();
+        for (k, v) in d {
+            let k = k
+                .downcast::<PyString>()
+                .map_err(|_| PyTypeError::new_err("keys must be str"))?;
+            m.insert(k.to_str()?.to_string(), from_python(&v, date_key)?);
+        }
+        Ok(Value::Object(m))
+    } else if obj.is_instance_of::<PyList>() || obj.is_instance_of::<PyTuple>() {
+        obj.iter()?
+            .map(|v| from_python(&v?, date_key))
+            .collect::<PyResult<Vec<_>>>()
+            .map(Value::Array)
+    } else if obj.is_instance_of::<PyDateTime>()
+        || date_key == TOML_DATE_KEY
+            && (obj.is_instance_of::<PyDate>() || obj.is_instance_of::<PyTime>())
+    {
+        let iso: String = obj.call_method0("isoformat")?.extract()?;
+        Ok(serde_json::json!({ date_key: iso }))
+    } else if obj.hasattr("isoformat")? {
+        Ok(Value::String(obj.call_method0("isoformat")?.extract()?))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "{} can't be written",
+            obj.get_type().name()?
+        )))
+    }
+}
+
+/// Converts the document from a format into another, e.g. `convert(text, "yaml", "json")`.
+///
+/// The document is `str` or `bytes`. The output is `bytes` for binary formats such as BSON, and
+/// `str` for others.
+#[pyfunction]
+#[pyo3(signature = (text, from_format, to_format, indent = None))]
+fn convert(
+    py: Python<'_>,
+    text: &Bound<'_, PyAny>,
+    from_format: &str,
+    to_format: &str,
+    indent: Option<usize>,
+) -> PyResult<PyObject> {
+    let to = format(to_format)?;
+    let bytes = convert_bytes(&bytes_of(text)?, format(from_format)?, to, indent)?;
+    output(py, to, bytes)
+}
+
+/// Reads the document into dicts, lists, strs, ints, floats, bools and None.
+#[pyfunction]
+fn loads(py: Python<'_>, text: &Bound<'_, PyAny>, format: &str) -> PyResult<PyObject> {
+    to_python(py, &value_of(text, format)?)
+}
+
+/// Writes the object in the format.
+#[pyfunction]
+#[pyo3(signature = (obj, format, indent = None))]
+fn dumps(
+    py: Python<'_>,
+    obj: &Bound<'_, PyAny>,
+    format: &str,
+    indent: Option<usize>,
+) -> PyResult<PyObject> {
+    let to = self::format(format)?;
+    let date_key = match to {
+        FileFormat::Toml => TOML_DATE_KEY,
+        _ => DATE_KEY,
+    };
+    let json = serde_json::to_vec(&from_python(obj, date_key)?)
+        .map_err(|e| RefmtError::new_err(e.to_string()))?;
+    output(py, to, convert_bytes(&json, FileFormat::Json, to, indent)?)
+}
+
+/// Returns the value at the JSON pointer, e.g. `"/author/name"`, or raises `KeyError`.
+#[pyfunction]
+fn query(
+    py: Python<'_>,
+    text: &Bound<'_, PyAny>,
+    format: &str,
+    pointer: &str,
+) -> PyResult<PyObject> {
+    let value = value_of(text, format)?;
+    match value.pointer(pointer) {
+        Some(v) => to_python(py, v),
+        None => Err(PyKeyError::new_err(pointer.to_string())),
+    }
+}
+
+/// Checks that the document can be read in the format, or raises `RefmtError`.
+#[pyfunction]
+fn validate(text: &Bound<'_, PyAny>, format: &str) -> PyResult<()> {
+    value_of(text, format).map(|_| ())
+}
+
+/// Returns the names of the formats.
+#[pyfunction]
+fn formats() -> Vec<&'static str> {
+    FileFormat::names()
+}
+
+#[pymodule]
+#[pyo3(name = "refmt")]
+fn refmt_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("RefmtError", m.py().get_type_bound::<RefmtError>())?;
+    m.add_function(wrap_pyfunction!(convert, m)?)?;
+    m.add_function(wrap_pyfunction!(loads, m)?)?;
+    m.add_function(wrap_pyfunction!(dumps, m)?)?;
+    m.add_function(wrap_pyfunction!(query, m)?)?;
+    m.add_function(wrap_pyfunction!(validate, m)?)?;
+    m.add_function(wrap_pyfunction!(formats, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+"""Tests of the built module, e.g. `maturin develop && python -m pytest tests`."""
+
+import datetime
+import unittest
+
+import refmt
+
+YAML = """\
+id: 123
+title: Lorem ipsum
+author:
+  name: John
+  tags:
+  - json
+  - yaml
+"""
+
+
+class ConvertTest(unittest.TestCase):
+    def test_convert(self):
+        self.assertEqual(
+            '{\n  "a": [\n    1,\n    2\n  ]\n}\n',
+            refmt.convert("a: [1, 2]", "yaml", "json"),
+        )
+        self.assertEqual("a:\n-   1\n", refmt.convert('{"a": [1]}', "json", "yml", indent=4))
+        self.assertEqual('id = 123\n', refmt.convert(b'{"id": 123}', "json", "toml"))
+
+    def test_binary(self):
+        bson = refmt.convert('{"a": 1}', "json", "bson")
+        self.assertIsInstance(bson, bytes)
+        self.assertEqual({"a": 1}, refmt.loads(bson, "bson"))
+
+    def test_errors(self):
+        with self.assertRaises(refmt.RefmtError):
+            refmt.convert("{", "json", "yaml")
+        with self.assertRaises(ValueError):
+            refmt.convert("{}", "conf", "yaml")
+        with self.assertRaises(TypeError):
+            refmt.convert(1, "json", "yaml")
+
+
+class ValueTest(unittest.TestCase):
+    def test_loads(self):
+        value = refmt.loads(YAML, "yaml")
+        author = {"name": "John", "tags": ["json", "yaml"]}
+        self.assertEqual({"id": 123, "title": "Lorem ipsum", "author": author}, value)
+        self.assertEqual(["id", "title", "author"], list(value))
+
+        # numbers are the same in every format.
+        for text, format in [("n = 1.0", "toml"), ("n: 1.0", "yaml"), ('{"n": 1.0}', "json")]:
+            self.assertEqual({"n": 1.0}, refmt.loads(text, format))
+            self.assertIsInstance(refmt.loads(text, format)["n"], float)
+
+    def test_dumps(self):
+        value = {"id": 123, "tags": ("a", "b"), "at": datetime.date(2020, 7, 27), "x": None}
+        self.assertEqual(
+            "id: 123\ntags:\n- a\n- b\nat: 2020-07-27\nx: null\n",
+            refmt.dumps(value, "yaml"),
+        )
+        self.assertEqual(
+            {**value, "tags": ["a", "b"], "at": "2020-07-27"},
+            refmt.loads(refmt.dumps(value, "json"), "json"),
+        )
+
+        with self.assertRaises(TypeError):
+            refmt.dumps({1: "a"}, "json")
+        with self.assertRaises(ValueError):
+            refmt.dumps(float("nan"), "json")
+
+    def test_dates(self):
+        utc = datetime.timezone.utc
+        at = datetime.datetime(2020, 7, 27, 5, 52, 30, tzinfo=utc)
+
+        # dates of every format are datetimes.
+        self.assertEqual({"at": at}, refmt.loads("at = 2020-07-27T05:52:30Z", "toml"))
+        self.assertEqual({"at": at}, refmt.loads('{"at": {"$date": "2020-07-27T05:52:30Z"}}', "json"))
+
+        # local dates and times of TOML are dates and times.
+        local = {"d": datetime.date(2020, 7, 27), "t": datetime.time(5, 52, 30)}
+        self.assertEqual(local, refmt.loads("d = 2020-07-27\nt = 05:52:30", "toml"))
+        self.assertEqual("d = 2020-07-27\nt = 05:52:30\n", refmt.dumps(local, "toml"))
+
+        # and written as the dates of the formats.
+        toml = refmt.dumps({"at": at}, "toml")
+        self.assertEqual("at = 2020-07-27T05:52:30+00:00\n", toml)
+        self.assertEqual({"at": at}, refmt.loads(toml, "toml"))
+        plist = refmt.dumps({"at": at}, "plist")
+        self.assertIn("<date>2020-07-27T05:52:30Z</date>", plist)
+        self.assertEqual({"at": at}, refmt.loads(plist, "plist"))
+        self.assertEqual({"at": at}, refmt.loads(refmt.dumps({"at": at}, "bplist"), "bplist"))
+
+    def test_query(self):
+        self.assertEqual("John", refmt.query(YAML, "yaml", "/author/name"))
+        self.assertEqual("yaml", refmt.query(YAML, "yaml", "/author/tags/1"))
+        with self.assertRaises(KeyError):
+            refmt.query(YAML, "yaml", "/author/email")
+
+    def test_validate(self):
+        refmt.validate(YAML, "yaml")
+        with self.assertRaises(refmt.RefmtError) as e:
+            refmt.validate("a = ", "toml")
+        self.assertIn("toml", str(e.exception))
+
+    def test_formats(self):
+        self.assertIn("yaml", refmt.formats())
+        self.assertIn("toml", refmt.formats())
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use refmt_serde::serde::{json::Json, yaml::Yaml, Serde};
+use refmt_serde::{Format, Refmt};
+
+/// A JSON array of `n` records, like an API response or a log.
+fn records(n: usize) -> Vec<u8> {
+    let records = (0..n)
+        .map(|i| {
+            serde_json::json!({
+                "id": i,
+                "name": format!("user-{}", i),
+                "active": i % 2 == 0,
+                "score": i as f64 / 7.0,
+                "tags": ["json", "yaml"],
+                "address": {"city": "Tokyo", "zip": null},
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_vec(&records).unwrap()
+}
+
+fn json_to(c: &mut Criterion, name: &str, dest_format: Format) {
+    let mut group = c.benchmark_group(name);
+    for n in [1_000, 10_000] {
+        let input = records(n);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("transcode", n), &input, |b, input| {
+            let refmt = Refmt::new(Format::Json, dest_format);
+            b.iter(|| refmt.refmt_bytes(input).unwrap())
+        });
+
+        // the conversion through a value of the destination, as before the transcoding.
+        group.bench_with_input(BenchmarkId::new("value", n), &input, |b, input| {
+            b.iter(|| match dest_format {
+                Format::Yaml => {
+                    let v = Json.deserialize(input).unwrap();
+                    Yaml.serialize(&v).unwrap()
+                }
+                _ => {
+                    let v = Json.deserialize(input).unwrap();
+                    Json.serialize(&v).unwrap()
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn json_to_yaml(c: &mut Criterion) {
+    json_to(c, "json_to_yaml", Format::Yaml);
+}
+
+fn json_to_json(c: &mut Criterion) {
+    json_to(c, "json_to_json", Format::Json);
+}
+
+criterion_group!(benches, json_to_yaml, json_to_json);
+criterion_main!(benches);
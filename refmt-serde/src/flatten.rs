@@ -0,0 +1,416 @@
+//! Flattening of nested documents into `a.b.c` keys, and the reverse, for key-value stores.
+//!
+//! ```yaml
+//! # nested
+//! db:
+//!   hosts: [a, b]
+//! # flattened, with the dot index style
+//! db.hosts.0: a
+//! db.hosts.1: b
+//! # flattened, with the bracket index style
+//! db.hosts[0]: a
+//! db.hosts[1]: b
+//! ```
+
+use std::collections::HashMap;
+
+use serde_yaml::{Mapping, Value};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlattenError {
+    #[error("the separator is empty")]
+    EmptySeparator,
+
+    #[error("keys must be scalars to flatten")]
+    Key,
+
+    /// A key is written twice, or has both a value and children.
+    #[error("conflicting key: {0}")]
+    Conflict(String),
+}
+
+/// How the indices of arrays are written in the keys.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum IndexStyle {
+    /// `a.0.b`, where number segments are read as indices.
+    #[default]
+    Dot,
+    /// `a[0].b`, where only bracketed segments are read as indices.
+    Bracket,
+}
+
+/// The keys of a flat document. The default is `a.b.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStyle {
+    separator: String,
+    index_style: IndexStyle,
+}
+
+impl Default for KeyStyle {
+    fn default() -> Self {
+        KeyStyle::new(".", IndexStyle::Dot)
+    }
+}
+
+/// Flattens the nested values, or unflattens the flat keys.
+///
+/// Empty objects and arrays are kept as values. Unflattened arrays must have all indices from 0,
+/// otherwise they are objects with the indices as keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flattening {
+    Flatten(KeyStyle),
+    Unflatten(KeyStyle),
+}
+
+impl Flattening {
+    pub fn apply(&self, v: Value) -> Result<Value, FlattenError> {
+        match self {
+            Flattening::Flatten(style) => style.flatten(v),
+            Flattening::Unflatten(style) => style.unflatten(v),
+        }
+    }
+}
+
+impl KeyStyle {
+    /// Returns the style of the separator, which must not be empty, and the index style.
+    pub fn new<S: Into<String>>(separator: S, index_style: IndexStyle) -> KeyStyle {
+        KeyStyle {
+            separator: separator.into(),
+            index_style,
+        }
+    }
+
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+
+    pub fn index_style(&self) -> IndexStyle {
+        self.index_style
+    }
+
+    /// Flattens the object or the array into an object. Other values are returned as they are.
+    pub fn flatten(&self, v: Value) -> Result<Value, FlattenError> {
+        if self.separator.is_empty() {
+            return Err(FlattenError::EmptySeparator);
+        }
+        if !has_children(&v) {
+            return Ok(v);
+        }
+        let mut flat = Mapping::new();
+        self.flatten_into(None, v, &mut flat)?;
+        Ok(Value::Mapping(flat))
+    }
+
+    fn flatten_into(
+        &self,
+        prefix: Option<String>,
+        v: Value,
+        flat: &mut Mapping,
+    ) -> Result<(), FlattenError> {
+        let join = |segment: String| match &prefix {
+            Some(p) => format!("{}{}{}", p, self.separator, segment),
+            None => segment,
+        };
+        match v {
+            Value::Mapping(m) if !m.is_empty() => {
+                for (k, v) in m {
+                    self.flatten_into(Some(join(key_string(k)?)), v, flat)?;
+                }
+            }
+            Value::Sequence(seq) if !seq.is_empty() => {
+                for (i, v) in seq.into_iter().enumerate() {
+                    let key = match (self.index_style, &prefix) {
+                        (IndexStyle::Bracket, Some(p)) => format!("{}[{}]", p, i),
+                        (IndexStyle::Bracket, None) => format!("[{}]", i),
+                        (IndexStyle::Dot, _) => join(i.to_string()),
+                    };
+                    self.flatten_into(Some(key), v, flat)?;
+                }
+            }
+            v => {
+                // e.g. `{"a.b": 1, "a": {"b": 2}}`, or keys which have the separator.
+                let key = prefix.unwrap_or_default();
+                if flat.contains_key(key.as_str()) {
+                    return Err(FlattenError::Conflict(key));
+                }
+                flat.insert(Value::String(key), v);
+            }
+        }
+        Ok(())
+    }
+
+    /// Unflattens the keys of the object. Other values are returned as they are.
+    pub fn unflatten(&self, v: Value) -> Result<Value, FlattenError> {
+        if self.separator.is_empty() {
+            return Err(FlattenError::EmptySeparator);
+        }
+        let flat = match v {
+            Value::Mapping(m) => m,
+            v => return Ok(v),
+        };
+
+        let mut root = Branch::default();
+        for (k, v) in flat {
+            let key = key_string(k)?;
+            let segments = self.segments(&key);
+            if !root.insert(&segments, v) {
+                return Err(FlattenError::Conflict(key));
+            }
+        }
+        Ok(root.into_value())
+    }
+
+    fn segments(&self, key: &str) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for part in key.split(self.separator.as_str()) {
+            match self.index_style {
+                IndexStyle::Dot => segments.push(match index(part) {
+                    Some(i) => Segment::Index(i),
+                    None => Segment::Key(part.to_string()),
+                }),
+                IndexStyle::Bracket => match bracketed(part) {
+                    Some((name, indices)) => {
+                        if !name.is_empty() {
+                            segments.push(Segment::Key(name.to_string()));
+                        }
+                        segments.extend(indices.into_iter().map(Segment::Index));
+                    }
+                    None => segments.push(Segment::Key(part.to_string())),
+                },
+            }
+        }
+        segments
+    }
+}
+
+fn has_children(v: &Value) -> bool {
+    match v {
+        Value::Mapping(m) => !m.is_empty(),
+        Value::Sequence(seq) => !seq.is_empty(),
+        _ => false,
+    }
+}
+
+fn key_string(k: Value) -> Result<String, FlattenError> {
+    match k {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(FlattenError::Key),
+    }
+}
+
+/// Returns the index if the segment is a number without leading zeros.
+fn index(segment: &str) -> Option<usize> {
+    segment
+        .parse::<usize>()
+        .ok()
+        .filter(|i| i.to_string() == segment)
+}
+
+/// Splits `name[0][1]` into the name and the indices.
+fn bracketed(part: &str) -> Option<(&str, Vec<usize>)> {
+    let start = part.find('[')?;
+    let (name, mut rest) = part.split_at(start);
+    let mut indices = Vec::new();
+    while !rest.is_empty() {
+        let (i, r) = rest.strip_prefix('[')?.split_once(']')?;
+        indices.push(index(i)?);
+        rest = r;
+    }
+    Some((name, indices))
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+enum Node {
+    Leaf(Value),
+    Branch(Branch),
+}
+
+/// The children of an unflattened value, in the order of the keys.
+#[derive(Default)]
+struct Branch {
+    positions: HashMap<Segment, usize>,
+    children: Vec<(Segment, Node)>,
+}
+
+impl Branch {
+    /// Inserts the value at the path, or returns false if the path has a value already.
+    fn insert(&mut self, path: &[Segment], v: Value) -> bool {
+        let (first, rest) = match path.split_first() {
+            Some(p) => p,
+            None => return false,
+        };
+        let position = match self.positions.get(first) {
+            Some(&p) => p,
+            None if rest.is_empty() => {
+                self.push(first.clone(), Node::Leaf(v));
+                return true;
+            }
+            None => self.push(first.clone(), Node::Branch(Branch::default())),
+        };
+        match &mut self.children[position].1 {
+            Node::Branch(b) if !rest.is_empty() => b.insert(rest, v),
+            _ => false,
+        }
+    }
+
+    fn push(&mut self, segment: Segment, node: Node) -> usize {
+        self.positions.insert(segment.clone(), self.children.len());
+        self.children.push((segment, node));
+        self.children.len() - 1
+    }
+
+    /// Returns an array if the segments are all indices from 0, or an object otherwise.
+    fn into_value(self) -> Value {
+        let mut indices = self
+            .children
+            .iter()
+            .map(|(s, _)| match s {
+                Segment::Index(i) => Some(*i),
+                Segment::Key(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default();
+        indices.sort_unstable();
+        let is_array = !indices.is_empty() && indices.iter().enumerate().all(|(n, &i)| n == i);
+
+        if is_array {
+            let mut children = self.children;
+            children.sort_by_key(|(s, _)| match s {
+                Segment::Index(i) => *i,
+                Segment::Key(_) => 0,
+            });
+            Value::Sequence(children.into_iter().map(|(_, n)| n.into_value()).collect())
+        } else {
+            let m = self
+                .children
+                .into_iter()
+                .map(|(s, n)| {
+                    let k = match s {
+                        Segment::Key(k) => k,
+                        Segment::Index(i) => i.to_string(),
+                    };
+                    (Value::String(k), n.into_value())
+                })
+                .collect();
+            Value::Mapping(m)
+        }
+    }
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::Leaf(v) => v,
+            Node::Branch(b) => b.into_value(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_yaml::Value;
+
+    use super::{FlattenError, IndexStyle, KeyStyle};
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    const NESTED: &str = r#"
+db:
+  hosts: [a, b]
+  port: 5432
+  options: {}
+matrix: [[1, 2]]
+"#;
+
+    #[test]
+    fn flatten() {
+        let dot = KeyStyle::default();
+        assert_eq!(
+            yaml(
+                r#"
+db.hosts.0: a
+db.hosts.1: b
+db.port: 5432
+db.options: {}
+matrix.0.0: 1
+matrix.0.1: 2
+"#
+            ),
+            dot.flatten(yaml(NESTED)).unwrap()
+        );
+
+        let bracket = KeyStyle::new("/", IndexStyle::Bracket);
+        assert_eq!(
+            yaml(
+                r#"
+db/hosts[0]: a
+db/hosts[1]: b
+db/port: 5432
+db/options: {}
+matrix[0][0]: 1
+matrix[0][1]: 2
+"#
+            ),
+            bracket.flatten(yaml(NESTED)).unwrap()
+        );
+        assert_eq!(yaml("'[0]': 1"), bracket.flatten(yaml("[1]")).unwrap());
+
+        // scalars are kept.
+        assert_eq!(yaml("1"), dot.flatten(yaml("1")).unwrap());
+        assert!(matches!(
+            dot.flatten(yaml("? [1]\n: a")),
+            Err(FlattenError::Key)
+        ));
+
+        // the flattened keys conflict.
+        assert!(matches!(
+            dot.flatten(yaml("a.b: 1\na:\n  b: 2")),
+            Err(FlattenError::Conflict(k)) if k == "a.b"
+        ));
+        assert!(matches!(
+            bracket.flatten(yaml("a: [1]\na[0]: 2")),
+            Err(FlattenError::Conflict(k)) if k == "a[0]"
+        ));
+    }
+
+    #[test]
+    fn unflatten() {
+        for index_style in [IndexStyle::Dot, IndexStyle::Bracket] {
+            let style = KeyStyle::new("__", index_style);
+            let flat = style.flatten(yaml(NESTED)).unwrap();
+            assert_eq!(yaml(NESTED), style.unflatten(flat).unwrap());
+        }
+
+        let dot = KeyStyle::default();
+        // sparse indices are keys.
+        assert_eq!(
+            yaml("a: {'0': x, '2': y}"),
+            dot.unflatten(yaml("a.0: x\na.2: y")).unwrap()
+        );
+        // numbers are keys of the bracket style.
+        let bracket = KeyStyle::new(".", IndexStyle::Bracket);
+        assert_eq!(
+            yaml("a: {'0': x}\nb: [[y]]"),
+            bracket.unflatten(yaml("a.0: x\nb[0][0]: y")).unwrap()
+        );
+
+        assert!(matches!(
+            dot.unflatten(yaml("a: 1\na.b: 2")),
+            Err(FlattenError::Conflict(k)) if k == "a.b"
+        ));
+        let empty = KeyStyle::new("", IndexStyle::Dot);
+        assert!(matches!(
+            empty.unflatten(yaml("a: 1")),
+            Err(FlattenError::EmptySeparator)
+        ));
+    }
+}
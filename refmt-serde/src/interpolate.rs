@@ -0,0 +1,216 @@
+//! Expansion of `${VAR}`, `${VAR:-default}` and `${file:path}` in the string values of a
+//! document.
+//!
+//! The expansion is done on the values after parsing, so that the quoting of the document is
+//! kept whatever the variables have. Keys and other scalars are not expanded, and an expanded
+//! value stays a string, e.g. `port: ${PORT}` is written as `port: '8080'` in YAML.
+//!
+//! `${file:path}` is disabled by default, since a document from others could read any file
+//! which the user can, e.g. `${file:/home/you/.ssh/id_rsa}`, into the output.
+
+use std::{env, fs, io};
+
+use serde_yaml::Value;
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterpolationError {
+    #[error("undefined variable: {0}")]
+    Undefined(String),
+
+    /// `${file:path}` is used without [`Interpolation::files`].
+    #[error("reading files is not enabled: {0}")]
+    FileDisabled(String),
+
+    #[error("can't read file: {path}, {cause}")]
+    File {
+        path: String,
+        #[source]
+        cause: io::Error,
+    },
+}
+
+/// The settings of the expansion.
+///
+/// - `${VAR}` is the environment variable, or an empty string if it's not defined.
+/// - `${VAR:-default}` is the default if the variable is not defined or empty.
+/// - `${file:path}` is the content of the file, without the trailing newline. A relative path is
+///   resolved from the current directory. It's an error unless [`Interpolation::files`] enables
+///   it, since any readable file can be read into the output.
+/// - `$${` is written as `${`, so that the expressions can be escaped.
+///
+/// Other expressions like `${var.name}` of HCL are kept as they are.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Interpolation {
+    strict: bool,
+    files: bool,
+}
+
+impl Interpolation {
+    pub fn new() -> Interpolation {
+        Interpolation::default()
+    }
+
+    /// Fails on undefined variables without defaults, instead of expanding them to empty strings.
+    pub fn strict(mut self, strict: bool) -> Interpolation {
+        self.strict = strict;
+        self
+    }
+
+    /// Reads the files of `${file:path}`. Enable it only for trusted documents.
+    pub fn files(mut self, files: bool) -> Interpolation {
+        self.files = files;
+        self
+    }
+
+    /// Expands the string values in the value and its children.
+    pub fn interpolate(&self, v: &mut Value) -> Result<(), InterpolationError> {
+        match v {
+            Value::String(s) => *s = self.expand(s)?,
+            Value::Sequence(seq) => {
+                for v in seq {
+                    self.interpolate(v)?;
+                }
+            }
+            Value::Mapping(m) => {
+                for v in m.values_mut() {
+                    self.interpolate(v)?;
+                }
+            }
+            Value::Tagged(t) => self.interpolate(&mut t.value)?,
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Expands the expressions in the string.
+    pub fn expand(&self, s: &str) -> Result<String, InterpolationError> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(i) = rest.find('$') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+
+            if let Some(r) = rest.strip_prefix("$${") {
+                out.push_str("${");
+                rest = r;
+                continue;
+            }
+            let expression = rest.strip_prefix("${").and_then(|r| r.split_once('}'));
+            let value = match expression {
+                Some((e, r)) => self.evaluate(e)?.map(|v| (v, r)),
+                None => None,
+            };
+            match value {
+                Some((v, r)) => {
+                    out.push_str(&v);
+                    rest = r;
+                }
+                // not an expression, which is written as is.
+                None => {
+                    out.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Returns the value of the expression, or None if it's not an expression to expand.
+    fn evaluate(&self, expression: &str) -> Result<Option<String>, InterpolationError> {
+        if let Some(path) = expression.strip_prefix("file:") {
+            if !self.files {
+                return Err(InterpolationError::FileDisabled(path.to_string()));
+            }
+            return read_file(path).map(Some);
+        }
+
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        if !is_variable_name(name) {
+            return Ok(None);
+        }
+        let value = env::var(name)
+            .ok()
+            .filter(|v| default.is_none() || !v.is_empty());
+        match (value, default) {
+            (Some(v), _) => Ok(Some(v)),
+            (None, Some(default)) => Ok(Some(default.to_string())),
+            (None, None) if self.strict => Err(InterpolationError::Undefined(name.to_string())),
+            (None, None) => Ok(Some(String::new())),
+        }
+    }
+}
+
+fn read_file(path: &str) -> Result<String, InterpolationError> {
+    let mut s = fs::read_to_string(path).map_err(|cause| InterpolationError::File {
+        path: path.to_string(),
+        cause,
+    })?;
+    if s.ends_with('\n') {
+        s.pop();
+        if s.ends_with('\r') {
+            s.pop();
+        }
+    }
+    Ok(s)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::{Interpolation, InterpolationError};
+
+    #[test]
+    fn expand() {
+        env::set_var("REFMT_EXPAND_HOST", "db.local");
+        env::set_var("REFMT_EXPAND_EMPTY", "");
+        let lenient = Interpolation::default();
+        let expand = |s: &str| lenient.expand(s).unwrap();
+
+        assert_eq!("db.local:5432", expand("${REFMT_EXPAND_HOST}:5432"));
+        assert_eq!("db.local", expand("${REFMT_EXPAND_HOST:-localhost}"));
+        assert_eq!("localhost", expand("${REFMT_EXPAND_EMPTY:-localhost}"));
+        assert_eq!("localhost", expand("${REFMT_EXPAND_UNDEFINED:-localhost}"));
+        assert_eq!("[]", expand("[${REFMT_EXPAND_UNDEFINED}]"));
+
+        // escaped, and not expressions.
+        assert_eq!("${REFMT_EXPAND_HOST}", expand("$${REFMT_EXPAND_HOST}"));
+        assert_eq!("app-${var.env}", expand("app-${var.env}"));
+        assert_eq!("$5 ${ $", expand("$5 ${ $"));
+
+        let strict = Interpolation::new().strict(true);
+        assert!(matches!(
+            strict.expand("${REFMT_EXPAND_UNDEFINED}"),
+            Err(InterpolationError::Undefined(name)) if name == "REFMT_EXPAND_UNDEFINED"
+        ));
+        assert_eq!("", strict.expand("${REFMT_EXPAND_UNDEFINED:-}").unwrap());
+    }
+
+    #[test]
+    fn expand_file() {
+        let path = env::temp_dir().join("refmt-expand-file.txt");
+        std::fs::write(&path, "s3cr3t\n").unwrap();
+        let expression = format!("${{file:{}}}", path.display());
+        let files = Interpolation::new().files(true);
+        assert_eq!("s3cr3t", files.expand(&expression).unwrap());
+
+        let r = files.expand("${file:/nonexistent/refmt}");
+        assert!(matches!(r, Err(InterpolationError::File { .. })));
+
+        // files are not read by default.
+        let r = Interpolation::default().expand(&expression);
+        assert!(matches!(r, Err(InterpolationError::FileDisabled(_))));
+    }
+}
@@ -1,69 +1,355 @@
+use std::io::{BufReader, BufWriter, Read, Write};
+
 use ::serde::Deserialize;
 
-use crate::serde::{json, toml, yaml, Serde};
+use crate::flatten::{FlattenError, Flattening};
+use crate::interpolate::{Interpolation, InterpolationError};
+use crate::serde::{
+    bson, edn, hcl, json, json5, kdl, plist, ron, table, toml, yaml, Serde, StreamError,
+};
 
+pub mod flatten;
+pub mod interpolate;
 pub mod serde;
 
 #[derive(Debug, Copy, Clone)]
 pub enum Format {
+    Bson,
+    Edn,
+    Hcl,
     Json,
+    Json5,
+    Jsonc,
+    Kdl,
+    Plist,
+    BinaryPlist,
+    Ron,
     Toml,
     Yaml,
+    MarkdownTable,
+    HtmlTable,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum RefmtError {
-    #[error("json error.")]
+    #[error("bson error: {0}")]
+    Bson(#[from] <bson::Bson as Serde>::Error),
+
+    #[error("edn error: {0}")]
+    Edn(#[from] <edn::Edn as Serde>::Error),
+
+    #[error("hcl error: {0}")]
+    Hcl(#[from] <hcl::Hcl as Serde>::Error),
+
+    #[error("json error: {0}")]
     Json(#[from] <json::Json as Serde>::Error),
 
-    #[error("toml error.")]
+    #[error("json5 error: {0}")]
+    Json5(#[from] <json5::Json5 as Serde>::Error),
+
+    #[error("kdl error: {0}")]
+    Kdl(#[from] <kdl::Kdl as Serde>::Error),
+
+    #[error("plist error: {0}")]
+    Plist(#[from] <plist::Plist as Serde>::Error),
+
+    #[error("ron error: {0}")]
+    Ron(#[from] <ron::Ron as Serde>::Error),
+
+    #[error("table error: {0}")]
+    Table(#[from] <table::MarkdownTable as Serde>::Error),
+
+    #[error("toml error: {0}")]
     Toml(#[from] <toml::Toml as Serde>::Error),
 
-    #[error("yaml error.")]
+    #[error("yaml error: {0}")]
     Yaml(#[from] <yaml::Yaml as Serde>::Error),
+
+    #[error("output is not a text.")]
+    Utf8(#[from] std::string::FromUtf8Error),
+
+    #[error("can't read or write.")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Interpolation(#[from] InterpolationError),
+
+    #[error(transparent)]
+    Flatten(#[from] FlattenError),
 }
 
+impl<E> From<StreamError<E>> for RefmtError
+where
+    E: std::error::Error + 'static,
+    RefmtError: From<E>,
+{
+    fn from(e: StreamError<E>) -> Self {
+        match e {
+            StreamError::Io(e) => RefmtError::Io(e),
+            StreamError::Format(e) => RefmtError::from(e),
+        }
+    }
+}
+
+/// Converts documents from a format into another.
+///
+/// ```
+/// use refmt_serde::{Format, Refmt};
+///
+/// let yaml = Refmt::new(Format::Json, Format::Yaml).refmt(r#"{"a": 1}"#)?;
+/// assert_eq!("a: 1\n", yaml);
+/// # Ok::<(), refmt_serde::RefmtError>(())
+/// ```
 pub struct Refmt {
-    pub src_format: Format,
-    pub dest_format: Format,
+    src_format: Format,
+    dest_format: Format,
+    interpolation: Option<Interpolation>,
+    flattening: Option<Flattening>,
 }
 
 impl Refmt {
+    pub fn new(src_format: Format, dest_format: Format) -> Refmt {
+        Refmt {
+            src_format,
+            dest_format,
+            interpolation: None,
+            flattening: None,
+        }
+    }
+
+    /// Expands the variables in the string values, see [`Interpolation`].
+    pub fn interpolate(mut self, interpolation: Interpolation) -> Refmt {
+        self.interpolation = Some(interpolation);
+        self
+    }
+
+    /// Flattens or unflattens the keys, see [`Flattening`].
+    pub fn flatten(mut self, flattening: Flattening) -> Refmt {
+        self.flattening = Some(flattening);
+        self
+    }
+
     pub fn refmt(&self, s: &str) -> Result<String, RefmtError> {
-        let r = match self.dest_format {
-            Format::Json => self.to_json(s)?,
-            Format::Toml => self.to_toml(s)?,
-            Format::Yaml => self.to_yaml(s)?,
+        let b = self.refmt_bytes(s.as_bytes())?;
+        Ok(String::from_utf8(b)?)
+    }
+
+    pub fn refmt_bytes(&self, b: &[u8]) -> Result<Vec<u8>, RefmtError> {
+        let mut out = Vec::new();
+        self.refmt_stream(b, &mut out)?;
+        Ok(out)
+    }
+
+    /// Reads a document from the reader, and writes the converted document into the writer.
+    ///
+    /// Only JSON into JSON is streamed without holding the document in memory. The
+    /// other conversions build the whole value first: JSON and BSON are parsed from the reader,
+    /// and JSON, YAML and BSON are serialized into the writer, without buffering the text,
+    /// while the other formats buffer the text too. The writer is buffered internally. With an
+    /// interpolation or a flattening, the document is always read into a value.
+    pub fn refmt_stream<R: Read, W: Write>(&self, mut r: R, w: W) -> Result<(), RefmtError> {
+        let mut w = BufWriter::new(w);
+        if self.keeps_comments() && !self.transforms() {
+            let mut b = Vec::new();
+            r.read_to_end(&mut b)?;
+            w.write_all(&self.reformat(&b)?)?;
+            w.flush()?;
+            return Ok(());
+        }
+        if self.transcodes() && !self.transforms() {
+            self.transcode(r, &mut w)?;
+            w.flush()?;
+            return Ok(());
+        }
+
+        match self.dest_format {
+            Format::Bson => self.to_bson(r, &mut w)?,
+            Format::Edn => self.to_edn(r, &mut w)?,
+            Format::Hcl => self.to_hcl(r, &mut w)?,
+            Format::Json => self.to_json(r, &mut w)?,
+            Format::Json5 => self.to_json5(r, &mut w)?,
+            Format::Jsonc => self.to_jsonc(r, &mut w)?,
+            Format::Kdl => self.to_kdl(r, &mut w)?,
+            Format::Plist => self.to_plist(r, &mut w)?,
+            Format::BinaryPlist => self.to_binary_plist(r, &mut w)?,
+            Format::Ron => self.to_ron(r, &mut w)?,
+            Format::Toml => self.to_toml(r, &mut w)?,
+            Format::Yaml => self.to_yaml(r, &mut w)?,
+            Format::MarkdownTable => self.to_markdown_table(r, &mut w)?,
+            Format::HtmlTable => self.to_html_table(r, &mut w)?,
         };
-        Ok(r)
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Returns true if the value is transformed between reading and writing.
+    fn transforms(&self) -> bool {
+        self.interpolation.is_some() || self.flattening.is_some()
+    }
+
+    /// Comments are kept only if the destination can have all syntaxes of the source.
+    fn keeps_comments(&self) -> bool {
+        matches!(
+            (self.src_format, self.dest_format),
+            (Format::Jsonc, Format::Jsonc) | (Format::Jsonc | Format::Json5, Format::Json5)
+        )
+    }
+
+    /// Re-indents the document keeping the comments, in the syntax of the destination.
+    fn reformat(&self, b: &[u8]) -> Result<Vec<u8>, json5::Json5Error> {
+        match self.dest_format {
+            Format::Jsonc => json5::Jsonc.reformat(b),
+            _ => json5::Json5.reformat(b),
+        }
     }
 
-    fn to_json(&self, s: &str) -> Result<String, RefmtError> {
-        let v = self.deserialize::<<json::Json as Serde>::ValueType>(s)?;
-        let s = json::Json.serialize(&v)?;
-        Ok(s)
+    /// JSON into JSON is transcoded without building a value. Duplicated keys are written as
+    /// they are, though the last one is kept when a value is built. The output may be written
+    /// partially if the source is broken.
+    ///
+    /// JSON into YAML is not transcoded, because duplicated keys would make the YAML invalid,
+    /// while they are rejected when a value is built. YAML is not transcoded either, because its
+    /// keys can be other than strings, and its tags are only kept through the value.
+    fn transcodes(&self) -> bool {
+        matches!(
+            (self.src_format, self.dest_format),
+            (Format::Json, Format::Json)
+        )
     }
 
-    fn to_toml(&self, s: &str) -> Result<String, RefmtError> {
-        let v = self.deserialize::<<toml::Toml as Serde>::ValueType>(s)?;
-        let s = toml::Toml.serialize(&v)?;
-        Ok(s)
+    /// Pipes the deserializer of the source into the serializer of the destination.
+    fn transcode<R: Read, W: Write>(&self, r: R, mut w: W) -> Result<(), RefmtError> {
+        let mut de = serde_json::Deserializer::from_reader(BufReader::new(r));
+        let mut ser = serde_json::Serializer::pretty(&mut w);
+        serde_transcode::transcode(&mut de, &mut ser)?;
+        w.write_all(b"\n")?;
+        Ok(de.end()?)
     }
 
-    fn to_yaml(&self, s: &str) -> Result<String, RefmtError> {
-        let v = self.deserialize::<<yaml::Yaml as Serde>::ValueType>(s)?;
-        let s = yaml::Yaml.serialize(&v)?;
-        Ok(s)
+    fn to_bson<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <bson::Bson as Serde>::ValueType>(r)?;
+        bson::Bson.serialize_to(&v, w)?;
+        Ok(())
     }
 
-    fn deserialize<T>(&self, s: &str) -> Result<T, RefmtError>
+    fn to_edn<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <edn::Edn as Serde>::ValueType>(r)?;
+        edn::Edn.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_hcl<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <hcl::Hcl as Serde>::ValueType>(r)?;
+        hcl::Hcl.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_json<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <json::Json as Serde>::ValueType>(r)?;
+        json::Json.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_json5<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <json5::Json5 as Serde>::ValueType>(r)?;
+        json5::Json5.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_jsonc<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <json5::Jsonc as Serde>::ValueType>(r)?;
+        json5::Jsonc.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_kdl<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <kdl::Kdl as Serde>::ValueType>(r)?;
+        kdl::Kdl.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_plist<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <plist::Plist as Serde>::ValueType>(r)?;
+        plist::Plist.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_binary_plist<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <plist::BinaryPlist as Serde>::ValueType>(r)?;
+        plist::BinaryPlist.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_ron<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <ron::Ron as Serde>::ValueType>(r)?;
+        ron::Ron.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_toml<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <toml::Toml as Serde>::ValueType>(r)?;
+        toml::Toml.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_yaml<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <yaml::Yaml as Serde>::ValueType>(r)?;
+        yaml::Yaml.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_markdown_table<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <table::MarkdownTable as Serde>::ValueType>(r)?;
+        table::MarkdownTable.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn to_html_table<R: Read, W: Write>(&self, r: R, w: W) -> Result<(), RefmtError> {
+        let v = self.deserialize::<_, <table::HtmlTable as Serde>::ValueType>(r)?;
+        table::HtmlTable.serialize_to(&v, w)?;
+        Ok(())
+    }
+
+    fn deserialize<R, T>(&self, r: R) -> Result<T, RefmtError>
     where
+        R: Read,
+        T: for<'de> Deserialize<'de>,
+    {
+        if !self.transforms() {
+            return self.read(r);
+        }
+
+        // YAML values are used to keep tags and non-string keys, which JSON values can't have.
+        let mut v = self.read::<_, serde_yaml::Value>(r)?;
+        if let Some(interpolation) = self.interpolation {
+            interpolation.interpolate(&mut v)?;
+        }
+        if let Some(flattening) = &self.flattening {
+            v = flattening.apply(v)?;
+        }
+        Ok(T::deserialize(v)?)
+    }
+
+    fn read<R, T>(&self, r: R) -> Result<T, RefmtError>
+    where
+        R: Read,
         T: for<'de> Deserialize<'de>,
     {
         let r = match self.src_format {
-            Format::Json => json::Json.deserialize(s)?,
-            Format::Toml => toml::Toml.deserialize(s)?,
-            Format::Yaml => yaml::Yaml.deserialize(s)?,
+            Format::Bson => bson::Bson.deserialize_from(r)?,
+            Format::Edn => edn::Edn.deserialize_from(r)?,
+            Format::Hcl => hcl::Hcl.deserialize_from(r)?,
+            Format::Json => json::Json.deserialize_from(r)?,
+            Format::Json5 => json5::Json5.deserialize_from(r)?,
+            Format::Jsonc => json5::Jsonc.deserialize_from(r)?,
+            Format::Kdl => kdl::Kdl.deserialize_from(r)?,
+            Format::Plist => plist::Plist.deserialize_from(r)?,
+            Format::BinaryPlist => plist::BinaryPlist.deserialize_from(r)?,
+            Format::Ron => ron::Ron.deserialize_from(r)?,
+            Format::Toml => toml::Toml.deserialize_from(r)?,
+            Format::Yaml => yaml::Yaml.deserialize_from(r)?,
+            Format::MarkdownTable => table::MarkdownTable.deserialize_from(r)?,
+            Format::HtmlTable => table::HtmlTable.deserialize_from(r)?,
         };
         Ok(r)
     }
@@ -116,16 +402,414 @@ author:
             .trim_start()
             .to_string()
         });
+
+        pub static BSON: Lazy<Vec<u8>> = Lazy::new(|| {
+            use bson::spec::BinarySubtype;
+            use bson::{doc, oid::ObjectId, Binary, DateTime};
+
+            let d = doc! {
+                "_id": ObjectId::parse_str("5f1e6f9e8c1a2b3c4d5e6f70").unwrap(),
+                "created_at": DateTime::from_millis(1_595_829_150_000),
+                "payload": Binary { subtype: BinarySubtype::Generic, bytes: b"refmt".to_vec() },
+                "count": 3,
+            };
+            let mut b = Vec::new();
+            d.to_writer(&mut b).unwrap();
+            b
+        });
+
+        pub static EXTENDED_JSON: Lazy<String> = Lazy::new(|| {
+            r#"
+{
+  "_id": {
+    "$oid": "5f1e6f9e8c1a2b3c4d5e6f70"
+  },
+  "created_at": {
+    "$date": "2020-07-27T05:52:30Z"
+  },
+  "payload": {
+    "$binary": {
+      "base64": "cmVmbXQ=",
+      "subType": "00"
+    }
+  },
+  "count": 3
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static HCL: Lazy<String> = Lazy::new(|| {
+            r#"
+region = "us-east-1"
+tags = {
+  env = "prod"
+}
+name = "app-${var.env}"
+
+provider "aws" {
+  region = var.region
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "${var.prefix}-logs"
+  count = var.enabled ? 1 : 0
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static HCL_YAML: Lazy<String> = Lazy::new(|| {
+            r#"
+region: us-east-1
+tags:
+  env: prod
+name: app-${var.env}
+provider:
+  aws:
+    region: ${var.region}
+resource:
+  aws_s3_bucket:
+    logs:
+      bucket: ${var.prefix}-logs
+      count: '${var.enabled ? 1 : 0}'
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static JSON5: Lazy<String> = Lazy::new(|| {
+            r#"
+// JSON5 allows comments,
+{
+  id: 123, // unquoted keys,
+  'title': 'Lorem ipsum dolor sit amet', // single quoted strings,
+  author: {id: 999, first_name: "John", last_name: "Doe",}, // and trailing commas.
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static JSONC: Lazy<String> = Lazy::new(|| {
+            r#"
+// the document
+{
+  "id": 123, // id
+  "title": "Lorem ipsum dolor sit amet",
+  /* the author */
+  "author": {
+    "id": 999,
+    "first_name": /* given name */ "John",
+    "last_name": "Doe"
+  },
+  "tags": []
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static RON: Lazy<String> = Lazy::new(|| {
+            r#"
+(
+    name: "refmt",
+    version: Version(0, 3, 0),
+    kind: Cli,
+    tags: [
+        "json",
+        "yaml",
+    ],
+    author: Some(Author(
+        name: "John Doe",
+        email: None,
+    )),
+    limits: {
+        "size": 1024,
+    },
+    ids: {
+        1: 'a',
+    },
+    pair: (1.5, -2),
+)
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static RON_JSON: Lazy<String> = Lazy::new(|| {
+            r#"
+{
+  "name": "refmt",
+  "version": {
+    "$name": "Version",
+    "$tuple": [
+      0,
+      3,
+      0
+    ]
+  },
+  "kind": {
+    "$name": "Cli"
+  },
+  "tags": [
+    "json",
+    "yaml"
+  ],
+  "author": {
+    "$name": "Some",
+    "$tuple": [
+      {
+        "$name": "Author",
+        "name": "John Doe",
+        "email": null
+      }
+    ]
+  },
+  "limits": {
+    "$map": {
+      "size": 1024
     }
+  },
+  "ids": {
+    "$map": [
+      [
+        1,
+        {
+          "$char": "a"
+        }
+      ]
+    ]
+  },
+  "pair": {
+    "$tuple": [
+      1.5,
+      -2
+    ]
+  }
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static KDL: Lazy<String> = Lazy::new(|| {
+            r#"
+package {
+    name "refmt"
+    version "0.3.0"
+    (person)author "John Doe" email="john@example.com"
+    released (date)"2020-07-27"
+}
+dependencies {
+    serde "1.0" default-features=false optional=true
+    syntect "4.7"
+    features {}
+}
+"#
+            .trim_start()
+            .to_string()
+        });
 
-    use crate::{Format, Refmt};
-    use fixtures::{JSON, TOML, YAML};
+        pub static KDL_YAML: Lazy<String> = Lazy::new(|| {
+            r#"
+- name: package
+  children:
+  - name: name
+    arguments:
+    - refmt
+  - name: version
+    arguments:
+    - 0.3.0
+  - name: author
+    type: person
+    arguments:
+    - John Doe
+    properties:
+      email: john@example.com
+  - name: released
+    arguments:
+    - $type: date
+      $value: 2020-07-27
+- name: dependencies
+  children:
+  - name: serde
+    arguments:
+    - '1.0'
+    properties:
+      default-features: false
+      optional: true
+  - name: syntect
+    arguments:
+    - '4.7'
+  - name: features
+    children: []
+"#
+            .trim_start()
+            .to_string()
+        });
 
-    fn refmt(src_format: Format, dest_format: Format) -> Refmt {
-        Refmt {
-            src_format,
-            dest_format,
+        pub static EDN: Lazy<String> = Lazy::new(|| {
+            r#"
+{:name "refmt",
+ :version [0 3 0],
+ :tags #{"json" "yaml"},
+ :author {:name "John Doe", :email nil},
+ :released #inst "2020-07-27T05:52:30Z",
+ :limits {"size" 1024, :ratio 1.5M},
+ :main refmt.core/-main,
+ :args (:from :to),
+ :sep \newline,
+ :uuid #uuid "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static EDN_JSON: Lazy<String> = Lazy::new(|| {
+            r#"
+{
+  "name": "refmt",
+  "version": [
+    0,
+    3,
+    0
+  ],
+  "tags": {
+    "$set": [
+      "json",
+      "yaml"
+    ]
+  },
+  "author": {
+    "name": "John Doe",
+    "email": null
+  },
+  "released": {
+    "$date": "2020-07-27T05:52:30Z"
+  },
+  "limits": {
+    "$map": [
+      [
+        "size",
+        1024
+      ],
+      [
+        {
+          "$keyword": "ratio"
+        },
+        {
+          "$numberDecimal": "1.5"
         }
+      ]
+    ]
+  },
+  "main": {
+    "$symbol": "refmt.core/-main"
+  },
+  "args": {
+    "$list": [
+      {
+        "$keyword": "from"
+      },
+      {
+        "$keyword": "to"
+      }
+    ]
+  },
+  "sep": {
+    "$char": "\n"
+  },
+  "uuid": {
+    "$tag": "uuid",
+    "$value": "f81d4fae-7dec-11d0-a765-00a0c91e6bf6"
+  }
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static RECORDS_YAML: Lazy<String> = Lazy::new(|| {
+            r#"
+- host: web-1
+  port: 8080
+  tags: [frontend, "a|b"]
+- host: db-1
+  port: 5432
+  replica:
+    of: db-0
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static PLIST: Lazy<String> = Lazy::new(|| {
+            r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>CFBundleIdentifier</key>
+	<string>com.example.refmt</string>
+	<key>LSMinimumSystemVersion</key>
+	<real>10.15</real>
+	<key>BuildDate</key>
+	<date>2020-07-27T05:52:30Z</date>
+	<key>Icon</key>
+	<data>
+	cmVmbXQ=
+	</data>
+	<key>Entitlements</key>
+	<array>
+		<true/>
+		<integer>42</integer>
+	</array>
+</dict>
+</plist>
+"#
+            .trim_start()
+            .to_string()
+        });
+
+        pub static PLIST_JSON: Lazy<String> = Lazy::new(|| {
+            r#"
+{
+  "CFBundleIdentifier": "com.example.refmt",
+  "LSMinimumSystemVersion": 10.15,
+  "BuildDate": {
+    "$date": "2020-07-27T05:52:30Z"
+  },
+  "Icon": {
+    "$binary": {
+      "base64": "cmVmbXQ=",
+      "subType": "00"
+    }
+  },
+  "Entitlements": [
+    true,
+    42
+  ]
+}
+"#
+            .trim_start()
+            .to_string()
+        });
+    }
+
+    use crate::flatten::{Flattening, KeyStyle};
+    use crate::interpolate::Interpolation;
+    use crate::serde::{json, json5, yaml, Serde};
+    use crate::{Format, Refmt, RefmtError};
+    use fixtures::{
+        BSON, EDN, EDN_JSON, EXTENDED_JSON, HCL, HCL_YAML, JSON, JSON5, JSONC, KDL, KDL_YAML,
+        PLIST, PLIST_JSON, RECORDS_YAML, RON, RON_JSON, TOML, YAML,
+    };
+
+    fn refmt(src_format: Format, dest_format: Format) -> Refmt {
+        Refmt::new(src_format, dest_format)
     }
 
     #[test]
@@ -190,4 +874,575 @@ author:
         let r = refmt.refmt(&YAML);
         assert_eq!(Some(YAML.to_string()), r.ok());
     }
+
+    #[test]
+    fn bson_to_json() {
+        let refmt = refmt(Format::Bson, Format::Json);
+        let r = refmt.refmt_bytes(&BSON);
+        assert_eq!(Some(EXTENDED_JSON.as_bytes().to_vec()), r.ok());
+    }
+
+    #[test]
+    fn json_to_bson() {
+        let refmt = refmt(Format::Json, Format::Bson);
+        let r = refmt.refmt(&EXTENDED_JSON);
+        assert!(r.is_err()); // BSON is not a text.
+
+        let r = refmt.refmt_bytes(EXTENDED_JSON.as_bytes());
+        assert_eq!(Some(BSON.to_vec()), r.ok());
+    }
+
+    #[test]
+    fn hcl_to_yaml() {
+        let refmt = refmt(Format::Hcl, Format::Yaml);
+        let r = refmt.refmt(&HCL);
+        assert_eq!(Some(HCL_YAML.to_string()), r.ok());
+    }
+
+    #[test]
+    fn yaml_to_hcl() {
+        let refmt = refmt(Format::Yaml, Format::Hcl);
+        let r = refmt.refmt(&HCL_YAML);
+        assert_eq!(Some(HCL.to_string()), r.ok());
+
+        let r = refmt.refmt("- not an object");
+        assert!(r.is_err());
+
+        // keys are not renamed, e.g. into `my_key`.
+        let r = refmt.refmt("my key: 1");
+        assert!(matches!(
+            r,
+            Err(RefmtError::Hcl(crate::serde::hcl::HclError::Key(k))) if k == "my key"
+        ));
+        let r = refmt.refmt("a:\n  my key: 1");
+        assert_eq!(Some("a = {\n  \"my key\" = 1\n}\n".to_string()), r.ok());
+    }
+
+    #[test]
+    fn json5_to_json() {
+        let refmt = refmt(Format::Json5, Format::Json);
+        let r = refmt.refmt(&JSON5);
+        assert_eq!(Some(JSON.to_string()), r.ok());
+    }
+
+    #[test]
+    fn json5_to_json5() {
+        let refmt = refmt(Format::Json5, Format::Json5);
+        let r = refmt.refmt(&JSON5);
+        assert_eq!(
+            Some(
+                r#"
+// JSON5 allows comments,
+{
+  id: 123, // unquoted keys,
+  'title': 'Lorem ipsum dolor sit amet', // single quoted strings,
+  author: {
+    id: 999,
+    first_name: "John",
+    last_name: "Doe"
+  } // and trailing commas.
+}
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+    }
+
+    #[test]
+    fn jsonc_to_jsonc() {
+        let refmt = refmt(Format::Jsonc, Format::Jsonc);
+        let r = refmt.refmt(&JSONC);
+        assert_eq!(Some(JSONC.to_string()), r.ok());
+
+        let r = refmt.refmt("{\"id\": 123 // no closing brace");
+        assert!(r.is_err());
+
+        // the syntaxes of JSON5 are not written into JSONC.
+        for json5 in ["{id: 1}", "{\"id\": 'a'}", "[0x10]", "[+1]"] {
+            let r = refmt.refmt(json5);
+            assert!(
+                matches!(r, Err(RefmtError::Json5(json5::Json5Error::Jsonc(_)))),
+                "{}",
+                json5
+            );
+        }
+        let r = Refmt::new(Format::Json5, Format::Json5).refmt("{id: 'a'}");
+        assert_eq!(Some("{\n  id: 'a'\n}\n".to_string()), r.ok());
+    }
+
+    #[test]
+    fn jsonc_to_yaml() {
+        let refmt = refmt(Format::Jsonc, Format::Yaml);
+        let r = refmt.refmt(&JSONC);
+        assert_eq!(Some(format!("{}tags: []\n", YAML.as_str())), r.ok());
+    }
+
+    #[test]
+    fn plist_to_json() {
+        let refmt = refmt(Format::Plist, Format::Json);
+        let r = refmt.refmt(&PLIST);
+        assert_eq!(Some(PLIST_JSON.to_string()), r.ok());
+    }
+
+    #[test]
+    fn json_to_plist() {
+        let refmt = refmt(Format::Json, Format::Plist);
+        let r = refmt.refmt(&PLIST_JSON);
+        assert_eq!(Some(PLIST.to_string()), r.ok());
+
+        let r = refmt.refmt("{\"key\": null}");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn binary_plist_to_plist() {
+        let r = refmt(Format::Plist, Format::BinaryPlist).refmt_bytes(PLIST.as_bytes());
+        assert!(r
+            .as_ref()
+            .map(|b| b.starts_with(b"bplist00"))
+            .unwrap_or(false));
+
+        let r = refmt(Format::BinaryPlist, Format::Plist).refmt_bytes(&r.unwrap());
+        assert_eq!(Some(PLIST.as_bytes().to_vec()), r.ok());
+    }
+
+    #[test]
+    fn plist_to_bson() {
+        let r = refmt(Format::Plist, Format::Bson).refmt_bytes(PLIST.as_bytes());
+        let d = bson::Document::from_reader(r.unwrap().as_slice()).unwrap();
+        assert_eq!(
+            1_595_829_150_000,
+            d.get_datetime("BuildDate").unwrap().timestamp_millis()
+        );
+        assert_eq!(b"refmt", d.get_binary_generic("Icon").unwrap().as_slice());
+    }
+
+    #[test]
+    fn ron_to_json() {
+        let refmt = refmt(Format::Ron, Format::Json);
+        let r = refmt.refmt(&RON);
+        assert_eq!(Some(RON_JSON.to_string()), r.ok());
+
+        let r = refmt.refmt("(name: \"refmt\"");
+        assert!(r.is_err());
+
+        // raw strings, byte strings and byte literals.
+        let r =
+            refmt.refmt(r###"(a: r#"raw "str""#, b: r"\n", c: b"a\x00\n", d: br"\x", e: b'a')"###);
+        assert_eq!(
+            Some(
+                r#"{
+  "a": "raw \"str\"",
+  "b": "\\n",
+  "c": {
+    "$bytes": [
+      97,
+      0,
+      10
+    ]
+  },
+  "d": {
+    "$bytes": [
+      92,
+      120
+    ]
+  },
+  "e": 97
+}
+"#
+                .to_string()
+            ),
+            r.ok()
+        );
+        let r = refmt.refmt(r#"(a: r#"unterminated")"#);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn json_to_ron() {
+        let r = refmt(Format::Json, Format::Ron).refmt(&RON_JSON).unwrap();
+        let r = refmt(Format::Ron, Format::Json).refmt(&r);
+        assert_eq!(Some(RON_JSON.to_string()), r.ok());
+
+        let refmt = refmt(Format::Json, Format::Ron);
+        let r = refmt.refmt(&JSON);
+        assert_eq!(
+            Some(
+                r#"
+(
+    id: 123,
+    title: "Lorem ipsum dolor sit amet",
+    author: (
+        id: 999,
+        first_name: "John",
+        last_name: "Doe",
+    ),
+)
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+
+        // a name is written only if the keys are identifiers.
+        let r = refmt.refmt(r#"{"$name": "X", "a b": 1, "c": {"$bytes": [97, 0]}}"#);
+        assert_eq!(
+            Some(
+                "{\n    \"$name\": \"X\",\n    \"a b\": 1,\n    \"c\": b\"a\\x00\",\n}\n"
+                    .to_string()
+            ),
+            r.ok()
+        );
+
+        // an empty object is an empty map, since `()` is an empty tuple.
+        let r = refmt.refmt(r#"{"a": {}, "b": []}"#);
+        assert_eq!(Some("(\n    a: {},\n    b: [],\n)\n".to_string()), r.ok());
+
+        // entries of a map must be pairs.
+        let r = refmt.refmt(r#"{"$map": [[1, "a"], [2]]}"#);
+        assert_eq!(
+            Some("ron error: ron map entries must be pairs of a key and a value: [2]".to_string()),
+            r.err().map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn bson_documents_to_yaml() {
+        let documents = [BSON.as_slice(), BSON.as_slice()].concat();
+        let r = refmt(Format::Bson, Format::Yaml).refmt_bytes(&documents);
+        assert!(r.is_ok());
+
+        let r = refmt(Format::Yaml, Format::Bson).refmt_bytes(&r.unwrap());
+        assert_eq!(Some(documents), r.ok());
+    }
+
+    #[test]
+    fn kdl_to_yaml() {
+        let refmt = refmt(Format::Kdl, Format::Yaml);
+        let r = refmt.refmt(&KDL);
+        assert_eq!(Some(KDL_YAML.to_string()), r.ok());
+
+        let r = refmt.refmt(
+            r##"
+// comments, slashdash and line continuations are skipped.
+package /* inline */ "refmt" /-"skipped" \
+    r#"raw "string""# 0x1F 1_000 -1.5e3 key=null /-{ skipped; }; empty {}
+"##,
+        );
+        assert_eq!(
+            Some(
+                r#"
+- name: package
+  arguments:
+  - refmt
+  - raw "string"
+  - 31
+  - 1000
+  - -1500.0
+  properties:
+    key: null
+- name: empty
+  children: []
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+
+        // a line continuation must end the line.
+        let r = refmt.refmt("node 1 \\ 2");
+        assert!(matches!(r, Err(RefmtError::Kdl(_))));
+        let r = refmt.refmt("node 1 \\ // comment\n  2");
+        assert!(r.is_ok());
+
+        // the message tells what is wrong.
+        let r = refmt.refmt("(u8 node 1");
+        assert_eq!(
+            Some("kdl error: can't parse kdl. line:1, column:4, cause:expected `)`".to_string()),
+            r.err().map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn yaml_to_kdl() {
+        let refmt = refmt(Format::Yaml, Format::Kdl);
+        let r = refmt.refmt(&KDL_YAML);
+        assert_eq!(Some(KDL.to_string()), r.ok());
+
+        let r = refmt.refmt("id: 123");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn edn_to_json() {
+        let refmt = refmt(Format::Edn, Format::Json);
+        let r = refmt.refmt(&EDN);
+        assert_eq!(Some(EDN_JSON.to_string()), r.ok());
+
+        let r = refmt.refmt("[1 #_2 3, 4N] ; comment");
+        assert_eq!(Some("[\n  1,\n  3,\n  4\n]\n".to_string()), r.ok());
+
+        let r = refmt.refmt("#_0 [1] #_2 ; comment\n #_{:a 3}");
+        assert_eq!(Some("[\n  1\n]\n".to_string()), r.ok());
+
+        let r = refmt.refmt("{:a}");
+        assert!(r.is_err());
+        let r = refmt.refmt("[1] #_");
+        assert!(r.is_err());
+
+        // the message tells what is wrong.
+        let r = refmt.refmt("# 1");
+        assert_eq!(
+            Some("edn error: can't parse edn. line:1, column:2, cause:expected a tag".to_string()),
+            r.err().map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn json_to_edn() {
+        let refmt = refmt(Format::Json, Format::Edn);
+        let r = refmt.refmt(&EDN_JSON);
+        assert_eq!(Some(EDN.to_string()), r.ok());
+
+        let r = refmt.refmt(&JSON);
+        assert_eq!(
+            Some(
+                r#"
+{:id 123,
+ :title "Lorem ipsum dolor sit amet",
+ :author {:id 999, :first_name "John", :last_name "Doe"}}
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+
+        // malformed tags are written as plain maps.
+        let r = refmt.refmt(r#"{"$map": [1]}"#);
+        assert_eq!(Some("{:$map [1]}\n".to_string()), r.ok());
+        let r = refmt.refmt(r#"{"$map": [[1]]}"#);
+        assert_eq!(Some("{:$map [[1]]}\n".to_string()), r.ok());
+    }
+
+    #[test]
+    fn yaml_to_markdown_table() {
+        let refmt = refmt(Format::Yaml, Format::MarkdownTable);
+        let r = refmt.refmt(&RECORDS_YAML);
+        assert_eq!(
+            Some(
+                r#"
+| host  | port | tags                | replica       |
+| ----- | ---- | ------------------- | ------------- |
+| web-1 | 8080 | ["frontend","a\|b"] |               |
+| db-1  | 5432 |                     | {"of":"db-0"} |
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+
+        let r = refmt.refmt("web: {port: 8080}\ndb: {port: 5432}");
+        assert_eq!(
+            Some(
+                r#"
+|     | port |
+| --- | ---- |
+| web | 8080 |
+| db  | 5432 |
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+
+        let r = refmt.refmt(r"- {path: 'C:\dir|x'}");
+        assert_eq!(
+            Some("| path       |\n| ---------- |\n| C:\\\\dir\\|x |\n".to_string()),
+            r.ok()
+        );
+
+        let r = refmt.refmt("[1, 2]");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn yaml_to_html_table() {
+        let refmt = refmt(Format::Yaml, Format::HtmlTable);
+        let r = refmt.refmt("- {name: <refmt>, version: 1}");
+        assert_eq!(
+            Some(
+                r#"
+<table>
+  <thead>
+    <tr>
+      <th>name</th>
+      <th>version</th>
+    </tr>
+  </thead>
+  <tbody>
+    <tr>
+      <td>&lt;refmt&gt;</td>
+      <td>1</td>
+    </tr>
+  </tbody>
+</table>
+"#
+                .trim_start()
+                .to_string()
+            ),
+            r.ok()
+        );
+    }
+
+    #[test]
+    fn markdown_table_to_json() {
+        let r = refmt(Format::MarkdownTable, Format::Json).refmt("| a |\n|---|\n| 1 |\n");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn stream_json_to_yaml() {
+        let mut out = Vec::new();
+        let r = refmt(Format::Json, Format::Yaml).refmt_stream(JSON.as_bytes(), &mut out);
+        assert!(r.is_ok());
+        assert_eq!(YAML.as_bytes(), out.as_slice());
+
+        // all documents are read, and written back.
+        let documents = [BSON.as_slice(), BSON.as_slice()].concat();
+        let mut out = Vec::new();
+        let r = refmt(Format::Bson, Format::Bson).refmt_stream(documents.as_slice(), &mut out);
+        assert!(r.is_ok());
+        assert_eq!(documents, out);
+    }
+
+    #[test]
+    fn stream_errors() {
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let r = refmt(Format::Json, Format::Yaml).refmt_stream(JSON.as_bytes(), Closed);
+        assert!(matches!(r, Err(RefmtError::Io(_))));
+
+        let r = refmt(Format::Json, Format::Yaml).refmt_stream("{".as_bytes(), Vec::new());
+        assert!(matches!(r, Err(RefmtError::Json(_))));
+    }
+
+    #[test]
+    fn transcode_json() {
+        let json = r#"{"a": [1, 2.5, -3, 18446744073709551615], "b": {"c": null, "d": "\u00e9"}}"#;
+        // the same as the output through the values.
+        let v: serde_json::Value = json::Json.deserialize(json.as_bytes()).unwrap();
+        let r = refmt(Format::Json, Format::Json).refmt_bytes(json.as_bytes());
+        assert_eq!(json::Json.serialize(&v).ok(), r.ok());
+
+        let v: serde_yaml::Value = json::Json.deserialize(json.as_bytes()).unwrap();
+        let r = refmt(Format::Json, Format::Yaml).refmt_bytes(json.as_bytes());
+        assert_eq!(yaml::Yaml.serialize(&v).ok(), r.ok());
+
+        let r = refmt(Format::Json, Format::Yaml).refmt(r#"{"a": 1e400}"#);
+        assert!(matches!(r, Err(RefmtError::Json(_))));
+
+        // duplicated keys are rejected the same as through the values, as YAML doesn't allow them.
+        let json = r#"{"a": 1, "a": 2}"#;
+        let r = refmt(Format::Json, Format::Yaml).refmt(json);
+        let through_value = refmt(Format::Json, Format::Yaml)
+            .interpolate(Interpolation::new())
+            .refmt(json);
+        assert_eq!(
+            Some("json error: duplicate entry with key \"a\" at line 1 column 13".to_string()),
+            r.err().map(|e| e.to_string())
+        );
+        assert_eq!(
+            Some("json error: duplicate entry with key \"a\" at line 1 column 13".to_string()),
+            through_value.err().map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn interpolate() {
+        std::env::set_var("REFMT_INTERPOLATE_ENV", "prod");
+        let interpolate = |src_format, dest_format, s: &str, strict| {
+            let interpolation = Interpolation::new().strict(strict);
+            refmt(src_format, dest_format)
+                .interpolate(interpolation)
+                .refmt(s)
+        };
+
+        // tags and the quoting of YAML are kept.
+        let yaml = "env: ${REFMT_INTERPOLATE_ENV}\nport: ${REFMT_INTERPOLATE_PORT:-8080}\nref: !Ref ${REFMT_INTERPOLATE_ENV}-bucket\n";
+        let r = interpolate(Format::Yaml, Format::Yaml, yaml, false);
+        assert_eq!(
+            "env: prod\nport: '8080'\nref: !Ref prod-bucket\n",
+            r.unwrap()
+        );
+
+        // the streamed and reformatted paths are also expanded.
+        let r = interpolate(
+            Format::Json,
+            Format::Yaml,
+            r#"{"a": "${REFMT_INTERPOLATE_ENV}"}"#,
+            false,
+        );
+        assert_eq!("a: prod\n", r.unwrap());
+        let r = interpolate(
+            Format::Jsonc,
+            Format::Jsonc,
+            r#"{"a": "${REFMT_INTERPOLATE_ENV}"}"#,
+            false,
+        );
+        assert_eq!("{\n  \"a\": \"prod\"\n}\n", r.unwrap());
+
+        let r = interpolate(
+            Format::Toml,
+            Format::Json,
+            "a = \"${REFMT_INTERPOLATE_UNDEFINED}\"",
+            false,
+        );
+        assert_eq!("{\n  \"a\": \"\"\n}\n", r.unwrap());
+        let r = interpolate(
+            Format::Toml,
+            Format::Json,
+            "a = \"${REFMT_INTERPOLATE_UNDEFINED}\"",
+            true,
+        );
+        assert!(matches!(r, Err(RefmtError::Interpolation(_))));
+    }
+
+    #[test]
+    fn flatten() {
+        let flattening = |src_format, dest_format, flattening| {
+            refmt(src_format, dest_format).flatten(flattening)
+        };
+        let style = KeyStyle::default();
+
+        let r = flattening(
+            Format::Json,
+            Format::Toml,
+            Flattening::Flatten(style.clone()),
+        )
+        .refmt(&JSON);
+        assert_eq!(
+            "id = 123\ntitle = \"Lorem ipsum dolor sit amet\"\n\"author.id\" = 999\n\"author.first_name\" = \"John\"\n\"author.last_name\" = \"Doe\"\n",
+            r.unwrap()
+        );
+
+        let flat = r#"{"id": 123, "title": "Lorem ipsum dolor sit amet", "author.id": 999, "author.first_name": "John", "author.last_name": "Doe"}"#;
+        let r = flattening(Format::Json, Format::Yaml, Flattening::Unflatten(style)).refmt(flat);
+        assert_eq!(YAML.to_string(), r.unwrap());
+    }
 }
@@ -0,0 +1,106 @@
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+
+use ::bson::{Bson as BsonValue, Document};
+use serde::Deserialize;
+
+use super::{Serde, StreamError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BsonError {
+    #[error("can't serialize into bson.")]
+    Serialize(#[from] ::bson::ser::Error),
+
+    #[error("can't deserialize from bson.")]
+    Deserialize(#[from] ::bson::de::Error),
+
+    #[error("can't read extended json.")]
+    ExtendedJson(#[from] ::bson::extjson::de::Error),
+
+    #[error("can't convert extended json.")]
+    Value(#[from] serde_json::Error),
+
+    #[error("bson requires a document or an array of documents.")]
+    NotDocument,
+}
+
+/// BSON documents, e.g. a `mongodump` output.
+///
+/// A single document is mapped to an object, and concatenated documents are mapped to an array.
+/// BSON specific types such as ObjectId, Date, Decimal128 and Binary are mapped to the relaxed
+/// extended JSON, e.g. `{"$oid": "..."}`, and mapped back to BSON types on serialization.
+pub struct Bson;
+
+impl Serde for Bson {
+    type Error = BsonError;
+    type ValueType = serde_json::Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut b = Vec::new();
+        for d in documents(v)? {
+            d.to_writer(&mut b)?;
+        }
+        Ok(b)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let mut reader = Cursor::new(b);
+        let mut documents = Vec::new();
+        while (reader.position() as usize) < b.len() {
+            let d = Document::from_reader(&mut reader)?;
+            documents.push(BsonValue::Document(d).into_relaxed_extjson());
+        }
+        from_documents(documents)
+    }
+
+    fn serialize_to<W: Write>(
+        &self,
+        v: &Self::ValueType,
+        mut w: W,
+    ) -> Result<(), StreamError<Self::Error>> {
+        for d in documents(v).map_err(StreamError::Format)? {
+            d.to_writer(&mut w)
+                .map_err(|e| StreamError::Format(e.into()))?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read, T: for<'de> Deserialize<'de>>(
+        &self,
+        r: R,
+    ) -> Result<T, StreamError<Self::Error>> {
+        let mut reader = BufReader::new(r);
+        let mut documents = Vec::new();
+        while !reader.fill_buf()?.is_empty() {
+            let d =
+                Document::from_reader(&mut reader).map_err(|e| StreamError::Format(e.into()))?;
+            documents.push(BsonValue::Document(d).into_relaxed_extjson());
+        }
+        from_documents(documents).map_err(StreamError::Format)
+    }
+}
+
+/// Splits the value into documents, a document for an object and each item for an array.
+fn documents(v: &serde_json::Value) -> Result<Vec<Document>, BsonError> {
+    match BsonValue::try_from(v.clone())? {
+        BsonValue::Document(d) => Ok(vec![d]),
+        BsonValue::Array(a) => a
+            .into_iter()
+            .map(|v| match v {
+                BsonValue::Document(d) => Ok(d),
+                _ => Err(BsonError::NotDocument),
+            })
+            .collect(),
+        _ => Err(BsonError::NotDocument),
+    }
+}
+
+fn from_documents<T: for<'de> Deserialize<'de>>(
+    mut documents: Vec<serde_json::Value>,
+) -> Result<T, BsonError> {
+    let v = if documents.len() == 1 {
+        documents.remove(0)
+    } else {
+        serde_json::Value::Array(documents)
+    };
+    Ok(serde_json::from_value(v)?)
+}
@@ -0,0 +1,504 @@
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+use super::scanner::Scanner;
+use super::{extjson, Serde};
+
+const KEYWORD_KEY: &str = "$keyword";
+const SYMBOL_KEY: &str = "$symbol";
+const CHAR_KEY: &str = "$char";
+const LIST_KEY: &str = "$list";
+const SET_KEY: &str = "$set";
+const MAP_KEY: &str = "$map";
+const TAG_KEY: &str = "$tag";
+const VALUE_KEY: &str = "$value";
+const DECIMAL_KEY: &str = "$numberDecimal";
+
+const WIDTH: usize = 80;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EdnError {
+    #[error("can't parse edn. line:{line}, column:{column}, cause:{message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("can't convert edn value. cause:{0}")]
+    Value(#[from] serde_json::Error),
+
+    #[error("edn must be a utf-8 text.")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+/// EDN, extensible data notation.
+///
+/// Maps whose keys are all keywords become JSON objects. EDN values which JSON doesn't have are
+/// mapped to tagged objects, so that an EDN document is written back as it was:
+///
+/// | EDN                     | JSON                                          |
+/// |-------------------------|-----------------------------------------------|
+/// | `{:a 1}`                | `{"a": 1}`                                    |
+/// | `{"a" 1}`               | `{"$map": {"a": 1}}`                          |
+/// | `{1 "a"}`               | `{"$map": [[1, "a"]]}`                        |
+/// | `:a`, `a`, `\c`         | `{"$keyword": "a"}`, `{"$symbol": "a"}`, `{"$char": "c"}` |
+/// | `(1 2)`, `#{1 2}`       | `{"$list": [1, 2]}`, `{"$set": [1, 2]}`       |
+/// | `1.5M`                  | `{"$numberDecimal": "1.5"}`                   |
+/// | `#inst "..."`           | `{"$date": "..."}`                            |
+/// | `#my/tag 1`             | `{"$tag": "my/tag", "$value": 1}`             |
+///
+/// Comments and `#_` discarded elements are not kept.
+pub struct Edn;
+
+impl Serde for Edn {
+    type Error = EdnError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut s = pretty(v, 0);
+        s.push('\n');
+        Ok(s.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let mut p = Parser {
+            s: Scanner::new(std::str::from_utf8(b)?),
+        };
+        let v = p.value()?;
+        while p.peek().is_some() && p.s.starts_with("#_") {
+            p.discard()?;
+        }
+        if p.peek().is_some() {
+            return Err(p.error("trailing characters"));
+        }
+        Ok(serde_json::from_value(v)?)
+    }
+}
+
+fn tagged(key: &str, v: Value) -> Value {
+    let mut m = Map::new();
+    m.insert(key.to_string(), v);
+    Value::Object(m)
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_alphanumeric() || "*+!-_?<>=./#:$%&'".contains(c)
+}
+
+fn is_symbol(s: &str) -> bool {
+    let mut chars = s.chars();
+    let first = chars.next();
+    let starts_like_number = match first {
+        Some(c) if c.is_ascii_digit() => true,
+        Some('+' | '-' | '.') => chars.next().is_some_and(|c| c.is_ascii_digit()),
+        _ => false,
+    };
+    first.is_some_and(|c| !":#'".contains(c))
+        && !starts_like_number
+        && s.chars().all(is_symbol_char)
+        && !matches!(s, "nil" | "true" | "false")
+}
+
+struct Parser<'a> {
+    s: Scanner<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> EdnError {
+        EdnError::Syntax {
+            line: self.s.line,
+            column: self.s.column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Skips whitespace, commas and comments, then peeks the next character.
+    fn peek(&mut self) -> Option<char> {
+        loop {
+            match self.s.peek() {
+                Some(c) if c.is_whitespace() || c == ',' => {
+                    self.s.next();
+                }
+                Some(';') => while !matches!(self.s.next(), Some('\n') | None) {},
+                c => return c,
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, EdnError> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('\\') => self.char(),
+            Some(':') => {
+                self.s.next();
+                Ok(tagged(KEYWORD_KEY, Value::String(self.token())))
+            }
+            Some('(') => Ok(tagged(LIST_KEY, Value::Array(self.sequence(')')?))),
+            Some('[') => Ok(Value::Array(self.sequence(']')?)),
+            Some('{') => self.map(),
+            Some('#') => self.dispatch(),
+            Some(c) if c.is_ascii_digit() => self.number(),
+            Some(c) if is_symbol_char(c) => {
+                let s = self.token();
+                match s.as_str() {
+                    "nil" => Ok(Value::Null),
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ if s.starts_with(['+', '-'])
+                        && s[1..].starts_with(|c: char| c.is_ascii_digit()) =>
+                    {
+                        self.number_from(s)
+                    }
+                    _ => Ok(tagged(SYMBOL_KEY, Value::String(s))),
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    /// Parses the elements until `close`, after skipping the opening character.
+    fn sequence(&mut self, close: char) -> Result<Vec<Value>, EdnError> {
+        self.s.next();
+        let mut values = Vec::new();
+        loop {
+            match self.peek() {
+                Some(c) if c == close => {
+                    self.s.next();
+                    return Ok(values);
+                }
+                None => return Err(self.error(&format!("expected `{}`", close))),
+                _ if self.s.starts_with("#_") => self.discard()?,
+                _ => values.push(self.value()?),
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Value, EdnError> {
+        let values = self.sequence('}')?;
+        if values.len() % 2 != 0 {
+            return Err(self.error("a map must have even number of forms"));
+        }
+        let keyword = |k: &Value| match k {
+            Value::Object(m) if m.len() == 1 => m
+                .get(KEYWORD_KEY)
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+
+        let entries = values.chunks(2);
+        if entries.clone().all(|kv| keyword(&kv[0]).is_some()) {
+            let m = entries
+                .map(|kv| (keyword(&kv[0]).unwrap(), kv[1].clone()))
+                .collect();
+            Ok(Value::Object(m))
+        } else if entries.clone().all(|kv| kv[0].is_string()) {
+            let m = entries
+                .map(|kv| (kv[0].as_str().unwrap().to_string(), kv[1].clone()))
+                .collect();
+            Ok(tagged(MAP_KEY, Value::Object(m)))
+        } else {
+            let pairs = entries.map(|kv| Value::Array(kv.to_vec())).collect();
+            Ok(tagged(MAP_KEY, Value::Array(pairs)))
+        }
+    }
+
+    /// Parses `#{...}`, `#_` and `#tag value`.
+    fn dispatch(&mut self) -> Result<Value, EdnError> {
+        if self.s.starts_with("#{") {
+            self.s.next();
+            return Ok(tagged(SET_KEY, Value::Array(self.sequence('}')?)));
+        }
+        if self.s.starts_with("#_") {
+            self.discard()?;
+            return self.value();
+        }
+
+        self.s.next();
+        let tag = self.token();
+        if tag.is_empty() {
+            return Err(self.error("expected a tag"));
+        }
+        let v = self.value()?;
+        match (tag.as_str(), v) {
+            ("inst", Value::String(s)) => Ok(extjson::date(s)),
+            (_, v) => {
+                let mut m = Map::new();
+                m.insert(TAG_KEY.to_string(), Value::String(tag));
+                m.insert(VALUE_KEY.to_string(), v);
+                Ok(Value::Object(m))
+            }
+        }
+    }
+
+    fn discard(&mut self) -> Result<(), EdnError> {
+        self.s.skip(2);
+        self.value()?;
+        Ok(())
+    }
+
+    fn token(&mut self) -> String {
+        self.s.take_while(is_symbol_char)
+    }
+
+    fn number(&mut self) -> Result<Value, EdnError> {
+        let s = self.token();
+        self.number_from(s)
+    }
+
+    fn number_from(&self, s: String) -> Result<Value, EdnError> {
+        let invalid = || self.error(&format!("invalid number `{}`", s));
+        if let Some(decimal) = s.strip_suffix('M') {
+            decimal.parse::<f64>().map_err(|_| invalid())?;
+            return Ok(tagged(
+                DECIMAL_KEY,
+                Value::String(decimal.trim_start_matches('+').to_string()),
+            ));
+        }
+
+        let digits = s.strip_suffix('N').unwrap_or(&s);
+        let n = if let Ok(i) = digits.parse::<i64>() {
+            Some(Number::from(i))
+        } else if let Ok(u) = digits.parse::<u64>() {
+            Some(Number::from(u))
+        } else if s.ends_with('N') {
+            return Err(self.error(&format!("`{}` is too large for json", s)));
+        } else {
+            digits.parse::<f64>().ok().and_then(Number::from_f64)
+        };
+        n.map(Value::Number).ok_or_else(invalid)
+    }
+
+    fn char(&mut self) -> Result<Value, EdnError> {
+        self.s.next();
+        let first = self
+            .s
+            .next()
+            .ok_or_else(|| self.error("unterminated char"))?;
+        let rest = self.s.take_while(|c| c.is_alphanumeric());
+        let c = match (first, rest.as_str()) {
+            (c, "") => c,
+            ('n', "ewline") => '\n',
+            ('s', "pace") => ' ',
+            ('t', "ab") => '\t',
+            ('r', "eturn") => '\r',
+            ('u', hex) if hex.len() == 4 => u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| self.error("invalid unicode char"))?,
+            (c, rest) => return Err(self.error(&format!("invalid char `\\{}{}`", c, rest))),
+        };
+        Ok(tagged(CHAR_KEY, Value::String(c.to_string())))
+    }
+
+    fn string(&mut self) -> Result<String, EdnError> {
+        self.s.next();
+        let mut s = String::new();
+        loop {
+            match self.s.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape()?),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, EdnError> {
+        let c = match self.s.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('u') => {
+                let hex: String = (0..4).filter_map(|_| self.s.next()).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            Some(c) => c,
+            None => return Err(self.error("unterminated escape")),
+        };
+        Ok(c)
+    }
+}
+
+/// Writes a value on one line if it fits in [`WIDTH`] from `column`, otherwise one element per
+/// line, aligned to the opening bracket as `clojure.pprint` does.
+fn pretty(v: &Value, column: usize) -> String {
+    let line = flat(v);
+    if column + line.chars().count() <= WIDTH {
+        return line;
+    }
+
+    match collection(v) {
+        Some((open, Items::Values(values))) => {
+            let indent = column + open.len();
+            let items = values.iter().map(|v| pretty(v, indent)).collect::<Vec<_>>();
+            wrap(open, &items, &format!("\n{}", " ".repeat(indent)))
+        }
+        Some((open, Items::Entries(entries))) => {
+            let indent = column + open.len();
+            let items = entries
+                .iter()
+                .map(|(k, v)| {
+                    let k = flat(k);
+                    let v = pretty(v, indent + k.chars().count() + 1);
+                    format!("{} {}", k, v)
+                })
+                .collect::<Vec<_>>();
+            wrap(open, &items, &format!(",\n{}", " ".repeat(indent)))
+        }
+        None => line,
+    }
+}
+
+fn flat(v: &Value) -> String {
+    match collection(v) {
+        Some((open, Items::Values(values))) => {
+            let items = values.iter().map(flat).collect::<Vec<_>>();
+            wrap(open, &items, " ")
+        }
+        Some((open, Items::Entries(entries))) => {
+            let items = entries
+                .iter()
+                .map(|(k, v)| format!("{} {}", flat(k), flat(v)))
+                .collect::<Vec<_>>();
+            wrap(open, &items, ", ")
+        }
+        None => scalar(v),
+    }
+}
+
+fn wrap(open: &str, items: &[String], separator: &str) -> String {
+    let close = match open {
+        "(" => ")",
+        "[" => "]",
+        _ => "}",
+    };
+    format!("{}{}{}", open, items.join(separator), close)
+}
+
+enum Items {
+    Values(Vec<Value>),
+    Entries(Vec<(Value, Value)>),
+}
+
+/// Returns the opening bracket and the elements if the value is written as a collection.
+fn collection(v: &Value) -> Option<(&'static str, Items)> {
+    match v {
+        Value::Array(values) => Some(("[", Items::Values(values.clone()))),
+        Value::Object(m) if m.len() == 1 => {
+            let (k, v) = m.iter().next().unwrap();
+            match (k.as_str(), v) {
+                (LIST_KEY, Value::Array(values)) => Some(("(", Items::Values(values.clone()))),
+                (SET_KEY, Value::Array(values)) => Some(("#{", Items::Values(values.clone()))),
+                (MAP_KEY, Value::Object(entries)) => Some((
+                    "{",
+                    Items::Entries(
+                        entries
+                            .iter()
+                            .map(|(k, v)| (Value::String(k.clone()), v.clone()))
+                            .collect(),
+                    ),
+                )),
+                // entries which are not pairs are written as a plain map of the tag.
+                (MAP_KEY, Value::Array(pairs)) => pairs
+                    .iter()
+                    .map(|kv| match kv {
+                        Value::Array(kv) if kv.len() == 2 => Some((kv[0].clone(), kv[1].clone())),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(|entries| ("{", Items::Entries(entries)))
+                    .or_else(|| Some(("{", object_entries(m)))),
+                _ if scalar_tag(m).is_some() => None,
+                _ => Some(("{", object_entries(m))),
+            }
+        }
+        Value::Object(m) if tagged_value(m).is_some() => None,
+        Value::Object(m) => Some(("{", object_entries(m))),
+        _ => None,
+    }
+}
+
+fn object_entries(m: &Map<String, Value>) -> Items {
+    Items::Entries(
+        m.iter()
+            .map(|(k, v)| {
+                let k = if is_symbol(k) {
+                    tagged(KEYWORD_KEY, Value::String(k.clone()))
+                } else {
+                    Value::String(k.clone())
+                };
+                (k, v.clone())
+            })
+            .collect(),
+    )
+}
+
+/// Returns the text of `{"$keyword": ...}` and the like, which are written as a scalar.
+fn scalar_tag(m: &Map<String, Value>) -> Option<String> {
+    if let Some(date) = extjson::as_date(m) {
+        return Some(format!("#inst {}", quote(date)));
+    }
+    let (k, v) = m.iter().next()?;
+    let s = v.as_str()?;
+    match k.as_str() {
+        KEYWORD_KEY if is_symbol(s) => Some(format!(":{}", s)),
+        SYMBOL_KEY if is_symbol(s) => Some(s.to_string()),
+        DECIMAL_KEY if s.parse::<f64>().is_ok() => Some(format!("{}M", s)),
+        CHAR_KEY if s.chars().count() == 1 => Some(match s {
+            "\n" => "\\newline".to_string(),
+            " " => "\\space".to_string(),
+            "\t" => "\\tab".to_string(),
+            "\r" => "\\return".to_string(),
+            _ => format!("\\{}", s),
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the tag and the value of `{"$tag": ..., "$value": ...}`.
+fn tagged_value(m: &Map<String, Value>) -> Option<(&str, &Value)> {
+    match (m.len(), m.get(TAG_KEY), m.get(VALUE_KEY)) {
+        (2, Some(Value::String(tag)), Some(v)) if is_symbol(tag) => Some((tag, v)),
+        _ => None,
+    }
+}
+
+fn scalar(v: &Value) -> String {
+    match v {
+        Value::Null => "nil".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote(s),
+        Value::Object(m) => match (scalar_tag(m), tagged_value(m)) {
+            (Some(s), _) => s,
+            (None, Some((tag, v))) => format!("#{} {}", tag, flat(v)),
+            (None, None) => unreachable!("an object is written as a map"),
+        },
+        Value::Array(_) => unreachable!("an array is written as a vector"),
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,47 @@
+//! Datetime and bytes representation for the formats which have such types.
+//!
+//! They are mapped to the relaxed extended JSON used by [`super::bson`], so that a value keeps its
+//! type when it is converted between those formats, e.g. from a plist `date` into a BSON date.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Map, Value};
+
+const DATE_KEY: &str = "$date";
+const BINARY_KEY: &str = "$binary";
+
+/// Returns `{"$date": "<RFC 3339>"}`.
+pub fn date(rfc3339: String) -> Value {
+    json!({ DATE_KEY: rfc3339 })
+}
+
+/// Returns `{"$binary": {"base64": "<base64>", "subType": "00"}}`.
+pub fn binary(bytes: &[u8]) -> Value {
+    json!({
+        BINARY_KEY: {
+            "base64": STANDARD.encode(bytes),
+            "subType": "00",
+        }
+    })
+}
+
+/// Returns the RFC 3339 text if the object is a datetime.
+pub fn as_date(m: &Map<String, Value>) -> Option<&str> {
+    single_entry(m, DATE_KEY).and_then(Value::as_str)
+}
+
+/// Returns the decoded bytes if the object is a bytes.
+pub fn as_binary(m: &Map<String, Value>) -> Option<Result<Vec<u8>, base64::DecodeError>> {
+    single_entry(m, BINARY_KEY)
+        .and_then(|v| v.get("base64"))
+        .and_then(Value::as_str)
+        .map(|s| STANDARD.decode(s))
+}
+
+fn single_entry<'a>(m: &'a Map<String, Value>, key: &str) -> Option<&'a Value> {
+    if m.len() == 1 {
+        m.get(key)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,163 @@
+use ::hcl::{Block, Body, Expression, Identifier, Object, ObjectKey, Structure};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::Serde;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HclError {
+    #[error("can't serialize into/deserialize from hcl.")]
+    Hcl(#[from] ::hcl::Error),
+
+    #[error("hcl requires an object at the top level.")]
+    NotObject,
+
+    /// Keys of attributes are identifiers, while keys in objects can be any strings.
+    #[error("hcl can't write the key as an attribute name: {0}")]
+    Key(String),
+}
+
+/// Top-level block types of Terraform and their number of labels.
+const BLOCK_TYPES: &[(&str, usize)] = &[
+    ("check", 1),
+    ("data", 2),
+    ("import", 0),
+    ("locals", 0),
+    ("module", 1),
+    ("moved", 0),
+    ("output", 1),
+    ("provider", 1),
+    ("resource", 2),
+    ("terraform", 0),
+    ("variable", 1),
+];
+
+/// HCL2, e.g. Terraform configurations and variables.
+///
+/// The input follows the [HCL JSON specification](https://github.com/hashicorp/hcl/blob/main/json/spec.md):
+/// a block is mapped to an object nested by its labels, e.g. `resource "a" "b" { ... }` to
+/// `{"resource": {"a": {"b": {...}}}}`, and repeated blocks with the same labels to an array.
+/// Expressions other than literals are mapped to strings, e.g. `var.name` to `"${var.name}"`.
+///
+/// On the output, since the JSON specification needs a schema to tell blocks from attributes,
+/// only the top-level Terraform block types such as `resource` and `variable` are written as
+/// blocks, with their number of labels. Anything else, including nested blocks, is written as an
+/// attribute. Keys of attributes must be identifiers. A string which consists of a single
+/// interpolation, e.g. `"${var.name}"`, is written as the expression itself.
+pub struct Hcl;
+
+impl Serde for Hcl {
+    type Error = HclError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let body = match v {
+            Value::Object(m) => body(m)?,
+            _ => return Err(HclError::NotObject),
+        };
+        let s = ::hcl::format::to_string(&body)?;
+        Ok(s.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let v = ::hcl::from_slice(b)?;
+        Ok(v)
+    }
+}
+
+fn body(m: &Map<String, Value>) -> Result<Body, HclError> {
+    let mut structures = Vec::new();
+    for (k, v) in m {
+        let labels = BLOCK_TYPES.iter().find(|(t, _)| t == k).map(|(_, n)| *n);
+        let mut blocks = Vec::new();
+        if labels.is_some_and(|n| collect_blocks(k, Vec::new(), n, v, &mut blocks)) {
+            structures.extend(blocks.into_iter().map(Structure::Block));
+        } else {
+            structures.push(Structure::Attribute(::hcl::Attribute::new(
+                attribute_key(k)?,
+                expression(v)?,
+            )));
+        }
+    }
+    Ok(Body::from(structures))
+}
+
+/// Returns false if the value doesn't have the shape of the blocks.
+fn collect_blocks(
+    identifier: &str,
+    labels: Vec<String>,
+    remaining: usize,
+    v: &Value,
+    blocks: &mut Vec<Block>,
+) -> bool {
+    match v {
+        Value::Array(a) => a
+            .iter()
+            .all(|v| collect_blocks(identifier, labels.clone(), remaining, v, blocks)),
+        Value::Object(m) if remaining > 0 => m.iter().all(|(k, v)| {
+            let mut labels = labels.clone();
+            labels.push(k.clone());
+            collect_blocks(identifier, labels, remaining - 1, v, blocks)
+        }),
+        Value::Object(m) => match body(m) {
+            Ok(body) => {
+                blocks.push(
+                    // the block types of Terraform are identifiers.
+                    Block::builder(Identifier::unchecked(identifier))
+                        .add_labels(labels)
+                        .add_structures(body)
+                        .build(),
+                );
+                true
+            }
+            Err(_) => false,
+        },
+        _ => false,
+    }
+}
+
+fn attribute_key(k: &str) -> Result<Identifier, HclError> {
+    Identifier::new(k).map_err(|_| HclError::Key(k.to_string()))
+}
+
+fn expression(v: &Value) -> Result<Expression, HclError> {
+    let e = match v {
+        Value::String(s) => template(s).unwrap_or_else(|| Expression::String(s.clone())),
+        Value::Array(a) => Expression::Array(a.iter().map(expression).collect::<Result<_, _>>()?),
+        Value::Object(m) => Expression::Object(
+            m.iter()
+                .map(|(k, v)| Ok((object_key(k), expression(v)?)))
+                .collect::<Result<Object<_, _>, HclError>>()?,
+        ),
+        _ => ::hcl::to_expression(v)?,
+    };
+    Ok(e)
+}
+
+fn object_key(k: &str) -> ObjectKey {
+    match Identifier::new(k) {
+        Ok(ident) => ObjectKey::Identifier(ident),
+        Err(_) => ObjectKey::from(k),
+    }
+}
+
+/// Parses a string with interpolations, e.g. `"${var.prefix}-logs"`, into a template.
+/// A string of a single interpolation, e.g. `"${var.name}"`, is parsed into `var.name`.
+fn template(s: &str) -> Option<Expression> {
+    if !s.contains("${") && !s.contains("%{") {
+        return None;
+    }
+
+    let single = s
+        .strip_prefix("${")
+        .and_then(|s| s.strip_suffix('}'))
+        .filter(|inner| !inner.contains("${") && !inner.contains("%{"));
+    single
+        .and_then(parse_expression)
+        .or_else(|| parse_expression(&serde_json::to_string(s).ok()?))
+}
+
+fn parse_expression(s: &str) -> Option<Expression> {
+    let body = ::hcl::parse(&format!("v = {}", s)).ok()?;
+    body.into_attributes().next().map(|attr| attr.expr)
+}
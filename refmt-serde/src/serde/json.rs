@@ -1,4 +1,6 @@
-use super::Serde;
+use std::io::{BufReader, Read, Write};
+
+use super::{Serde, StreamError};
 use serde::Deserialize;
 
 pub struct Json;
@@ -7,14 +9,31 @@ impl Serde for Json {
     type Error = serde_json::Error;
     type ValueType = serde_json::Value;
 
-    fn serialize(&self, v: &Self::ValueType) -> Result<String, Self::Error> {
-        let mut s = serde_json::to_string_pretty(v)?;
-        s.push('\n'); // add a new-line for consistency. (YAML and TOML have a new-line on its tail.)
-        Ok(s)
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut b = serde_json::to_vec_pretty(v)?;
+        b.push(b'\n'); // add a new-line for consistency. (YAML and TOML have a new-line on its tail.)
+        Ok(b)
     }
 
-    fn deserialize<T: for<'de> Deserialize<'de>>(&self, s: &str) -> Result<T, Self::Error> {
-        let v = serde_json::from_str(s)?;
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let v = serde_json::from_slice(b)?;
         Ok(v)
     }
+
+    fn serialize_to<W: Write>(
+        &self,
+        v: &Self::ValueType,
+        mut w: W,
+    ) -> Result<(), StreamError<Self::Error>> {
+        serde_json::to_writer_pretty(&mut w, v).map_err(StreamError::Format)?;
+        w.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn deserialize_from<R: Read, T: for<'de> Deserialize<'de>>(
+        &self,
+        r: R,
+    ) -> Result<T, StreamError<Self::Error>> {
+        serde_json::from_reader(BufReader::new(r)).map_err(StreamError::Format)
+    }
 }
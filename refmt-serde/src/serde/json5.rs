@@ -0,0 +1,250 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use serde::Deserialize;
+
+use super::Serde;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Json5Error {
+    #[error("can't serialize into json.")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error("can't deserialize from json5.")]
+    Deserialize(#[from] ::json5::Error),
+
+    #[error("json5 must be a utf-8 text.")]
+    Utf8(#[from] std::str::Utf8Error),
+
+    #[error("jsonc doesn't allow the syntax of json5: {0}")]
+    Jsonc(String),
+}
+
+/// JSON5, a lenient JSON which allows comments, trailing commas, unquoted keys and so on.
+///
+/// The output is a plain JSON, which is also a valid JSON5.
+pub struct Json5;
+
+/// JSON with comments, e.g. VS Code settings and tsconfig.
+///
+/// The input is read as JSON5. The output is a plain JSON, unless the source is also a JSON with
+/// comments. See [`Jsonc::reformat`].
+pub struct Jsonc;
+
+impl Serde for Json5 {
+    type Error = Json5Error;
+    type ValueType = serde_json::Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut b = serde_json::to_vec_pretty(v)?;
+        b.push(b'\n');
+        Ok(b)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let v = ::json5::from_str(std::str::from_utf8(b)?)?;
+        Ok(v)
+    }
+}
+
+impl Serde for Jsonc {
+    type Error = Json5Error;
+    type ValueType = serde_json::Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        Json5.serialize(v)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        Json5.deserialize(b)
+    }
+}
+
+impl Json5 {
+    /// Re-indents the document in the same style as the JSON output, keeping the comments.
+    ///
+    /// Tokens are written as they are, so unquoted keys and single-quoted strings are kept.
+    /// Trailing commas are removed.
+    pub fn reformat(&self, b: &[u8]) -> Result<Vec<u8>, Json5Error> {
+        reformat(b, false)
+    }
+}
+
+impl Jsonc {
+    /// Re-indents the document like [`Json5::reformat`], keeping the comments.
+    ///
+    /// The document must be a JSON with comments, since the tokens are written as they are.
+    /// Other syntaxes of JSON5, e.g. unquoted keys and single-quoted strings, are errors.
+    pub fn reformat(&self, b: &[u8]) -> Result<Vec<u8>, Json5Error> {
+        reformat(b, true)
+    }
+}
+
+fn reformat(b: &[u8], jsonc: bool) -> Result<Vec<u8>, Json5Error> {
+    let s = std::str::from_utf8(b)?;
+    Json5.deserialize::<serde::de::IgnoredAny>(b)?; // validate the document.
+
+    let tokens = tokenize(s);
+    if jsonc {
+        // strings, numbers and literals are tokens of JSON.
+        let atom = tokens.iter().find_map(|t| match *t {
+            Token::Atom(a) if serde_json::from_str::<serde::de::IgnoredAny>(a).is_err() => Some(a),
+            _ => None,
+        });
+        if let Some(a) = atom {
+            return Err(Json5Error::Jsonc(a.to_string()));
+        }
+    }
+    let mut f = Formatter::default();
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens[i + 1..].iter().find(|t| !t.is_comment());
+        match *token {
+            Token::Punct(c @ ('{' | '[')) => {
+                f.write(&c.to_string());
+                if !matches!(tokens.get(i + 1), Some(Token::Punct('}' | ']'))) {
+                    f.indent += 1;
+                    f.newline = true;
+                }
+            }
+            Token::Punct(c @ ('}' | ']')) => {
+                if !matches!(tokens[..i].last(), Some(Token::Punct('{' | '['))) {
+                    f.indent -= 1;
+                    f.newline = true;
+                }
+                f.write(&c.to_string());
+            }
+            Token::Punct(',') => {
+                if !matches!(next, Some(Token::Punct('}' | ']'))) {
+                    f.write(",");
+                }
+                f.newline = true;
+            }
+            Token::Punct(':') => f.write(": "),
+            Token::Punct(c) => f.write(&c.to_string()),
+            Token::Atom(s) => f.write(s),
+            Token::Comment { text, own_line } => f.comment(text, own_line),
+        }
+    }
+    f.out.push('\n');
+    Ok(f.out.into_bytes())
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Punct(char),
+    Atom(&'a str),
+    Comment { text: &'a str, own_line: bool },
+}
+
+impl<'a> Token<'a> {
+    fn is_comment(&self) -> bool {
+        matches!(self, Token::Comment { .. })
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    let mut own_line = true;
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\n' => own_line = true,
+            c if c.is_whitespace() => {}
+            '{' | '}' | '[' | ']' | ',' | ':' => {
+                tokens.push(Token::Punct(c));
+                own_line = false;
+            }
+            '"' | '\'' => {
+                let end = skip_string(&mut chars, c, s.len());
+                tokens.push(Token::Atom(&s[start..end]));
+                own_line = false;
+            }
+            '/' if matches!(chars.peek(), Some((_, '/' | '*'))) => {
+                let block = chars.next().map(|(_, c)| c) == Some('*');
+                let end = skip_comment(&mut chars, block, s.len());
+                tokens.push(Token::Comment {
+                    text: s[start..end].trim_end(),
+                    own_line,
+                });
+                own_line = !block;
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || "{}[],:\"'/".contains(c) {
+                        break;
+                    }
+                    chars.next();
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Atom(&s[start..end]));
+                own_line = false;
+            }
+        }
+    }
+    tokens
+}
+
+fn skip_string(chars: &mut Peekable<CharIndices>, quote: char, len: usize) -> usize {
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return i + 1;
+        }
+    }
+    len
+}
+
+fn skip_comment(chars: &mut Peekable<CharIndices>, block: bool, len: usize) -> usize {
+    while let Some((i, c)) = chars.next() {
+        if !block && c == '\n' {
+            return i;
+        }
+        if block && c == '*' && matches!(chars.peek(), Some((_, '/'))) {
+            chars.next();
+            return i + 2;
+        }
+    }
+    len
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+    indent: usize,
+    newline: bool,
+    space: bool,
+}
+
+impl Formatter {
+    fn write(&mut self, s: &str) {
+        if self.newline {
+            self.out.push('\n');
+            self.out.push_str(&"  ".repeat(self.indent));
+        } else if self.space {
+            self.out.push(' ');
+        }
+        self.newline = false;
+        self.space = false;
+        self.out.push_str(s);
+    }
+
+    fn comment(&mut self, text: &str, own_line: bool) {
+        if own_line || self.out.is_empty() {
+            self.newline = !self.out.is_empty();
+            self.write(text);
+        } else {
+            // a trailing comment stays on the same line.
+            if !self.out.ends_with(' ') {
+                self.out.push(' ');
+            }
+            self.out.push_str(text);
+            self.space = true;
+        }
+
+        if own_line || text.starts_with("//") {
+            self.newline = true;
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::scanner::{self, Scanner};
+use super::Serde;
+
+const NAME_KEY: &str = "name";
+const TYPE_KEY: &str = "type";
+const ARGUMENTS_KEY: &str = "arguments";
+const PROPERTIES_KEY: &str = "properties";
+const CHILDREN_KEY: &str = "children";
+
+const VALUE_TYPE_KEY: &str = "$type";
+const VALUE_KEY: &str = "$value";
+
+#[derive(Debug, thiserror::Error)]
+pub enum KdlError {
+    #[error("can't parse kdl. line:{line}, column:{column}, cause:{message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("can't serialize into kdl. cause:{0}")]
+    Node(String),
+
+    #[error("can't convert kdl value. cause:{0}")]
+    Value(#[from] serde_json::Error),
+
+    #[error("kdl must be a utf-8 text.")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+/// KDL document language.
+///
+/// A document is mapped to an array of nodes, and each node to an object. Empty fields are
+/// omitted, except `children` which is kept when the node has braces:
+///
+/// | KDL                              | JSON                                                     |
+/// |----------------------------------|----------------------------------------------------------|
+/// | `node 1 "a" key=true { child }`  | `{"name": "node", "arguments": [1, "a"],`                |
+/// |                                  | ` "properties": {"key": true}, "children": [...]}`       |
+/// | `(tag)node`                      | `{"name": "node", "type": "tag"}`                        |
+/// | `node (u8)1`                     | `{"name": "node", "arguments": [{"$type": "u8", "$value": 1}]}` |
+///
+/// Comments are not kept.
+pub struct Kdl;
+
+impl Serde for Kdl {
+    type Error = KdlError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut w = Writer::default();
+        w.nodes(v)?;
+        Ok(w.out.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let mut p = Parser {
+            s: Scanner::new(std::str::from_utf8(b)?),
+        };
+        let nodes = p.nodes(false)?;
+        Ok(serde_json::from_value(Value::Array(nodes))?)
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    !(c.is_whitespace() || c.is_control() || "\\/(){}<>;[]=,\"".contains(c))
+}
+
+fn starts_like_number(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() => true,
+        Some('+' | '-') => chars.next().is_some_and(|c| c.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty()
+        && !starts_like_number(s)
+        && s.chars().all(is_identifier_char)
+        && !matches!(s, "true" | "false" | "null")
+}
+
+enum Entry {
+    Argument(Value),
+    Property(String, Value),
+}
+
+struct Parser<'a> {
+    s: Scanner<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> KdlError {
+        KdlError::Syntax {
+            line: self.s.line,
+            column: self.s.column,
+            message: message.to_string(),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), KdlError> {
+        if self.s.peek() == Some(expected) {
+            self.s.next();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", expected)))
+        }
+    }
+
+    /// Skips `/* ... */`, which may be nested.
+    fn block_comment(&mut self) {
+        let mut depth = 0;
+        loop {
+            if self.s.starts_with("/*") {
+                self.s.skip(2);
+                depth += 1;
+            } else if self.s.starts_with("*/") {
+                self.s.skip(2);
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            } else if self.s.next().is_none() {
+                return;
+            }
+        }
+    }
+
+    fn line_comment(&mut self) {
+        while !matches!(self.s.peek(), Some('\n') | None) {
+            self.s.next();
+        }
+    }
+
+    /// Skips whitespace within a node, including line continuations. Returns true if any was.
+    fn skip_node_space(&mut self) -> Result<bool, KdlError> {
+        let mut skipped = false;
+        loop {
+            match self.s.peek() {
+                Some('\n') => return Ok(skipped),
+                Some(c) if c.is_whitespace() => {
+                    self.s.next();
+                }
+                Some('\\') => {
+                    self.s.next();
+                    while self
+                        .s
+                        .peek()
+                        .is_some_and(|c| c.is_whitespace() && c != '\n')
+                    {
+                        self.s.next();
+                    }
+                    if self.s.starts_with("//") {
+                        self.line_comment();
+                    }
+                    if !matches!(self.s.next(), Some('\n') | None) {
+                        return Err(self.error("expected a newline after `\\`"));
+                    }
+                }
+                _ if self.s.starts_with("/*") => self.block_comment(),
+                _ => return Ok(skipped),
+            }
+            skipped = true;
+        }
+    }
+
+    /// Skips whitespace, newlines, `;` and comments between nodes.
+    fn skip_line_space(&mut self) {
+        loop {
+            match self.s.peek() {
+                Some(c) if c.is_whitespace() || c == ';' => {
+                    self.s.next();
+                }
+                _ if self.s.starts_with("//") => self.line_comment(),
+                _ if self.s.starts_with("/*") => self.block_comment(),
+                _ => return,
+            }
+        }
+    }
+
+    fn nodes(&mut self, nested: bool) -> Result<Vec<Value>, KdlError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_line_space();
+            match self.s.peek() {
+                None if nested => return Err(self.error("expected `}`")),
+                None => return Ok(nodes),
+                Some('}') if nested => {
+                    self.s.next();
+                    return Ok(nodes);
+                }
+                _ if self.s.starts_with("/-") => {
+                    self.s.skip(2);
+                    self.skip_node_space()?;
+                    self.node()?;
+                }
+                _ => nodes.push(self.node()?),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Value, KdlError> {
+        let ty = self.type_annotation()?;
+        let name = self.identifier()?;
+
+        let mut arguments = Vec::new();
+        let mut properties = Map::new();
+        let mut children = None;
+        loop {
+            let spaced = self.skip_node_space()?;
+            match self.s.peek() {
+                None | Some('\n' | ';' | '}') => break,
+                _ if self.s.starts_with("//") => break,
+                Some('{') => {
+                    self.s.next();
+                    children = Some(self.nodes(true)?);
+                }
+                _ if self.s.starts_with("/-") => {
+                    self.s.skip(2);
+                    self.skip_node_space()?;
+                    if self.s.peek() == Some('{') {
+                        self.s.next();
+                        self.nodes(true)?;
+                    } else {
+                        self.entry()?;
+                    }
+                }
+                _ if !spaced => return Err(self.error("expected whitespace")),
+                _ => match self.entry()? {
+                    Entry::Argument(v) => arguments.push(v),
+                    Entry::Property(k, v) => {
+                        properties.insert(k, v);
+                    }
+                },
+            }
+        }
+
+        let mut m = Map::new();
+        m.insert(NAME_KEY.to_string(), Value::String(name));
+        if let Some(ty) = ty {
+            m.insert(TYPE_KEY.to_string(), Value::String(ty));
+        }
+        if !arguments.is_empty() {
+            m.insert(ARGUMENTS_KEY.to_string(), Value::Array(arguments));
+        }
+        if !properties.is_empty() {
+            m.insert(PROPERTIES_KEY.to_string(), Value::Object(properties));
+        }
+        if let Some(children) = children {
+            m.insert(CHILDREN_KEY.to_string(), Value::Array(children));
+        }
+        Ok(Value::Object(m))
+    }
+
+    fn type_annotation(&mut self) -> Result<Option<String>, KdlError> {
+        if self.s.peek() != Some('(') {
+            return Ok(None);
+        }
+        self.s.next();
+        let ty = self.identifier()?;
+        self.expect(')')?;
+        Ok(Some(ty))
+    }
+
+    fn entry(&mut self) -> Result<Entry, KdlError> {
+        if self.s.peek() == Some('(') {
+            return Ok(Entry::Argument(self.value()?));
+        }
+
+        let key = match self.s.peek() {
+            Some('"') => self.string()?,
+            _ if self.s.starts_with("r\"") || self.s.starts_with("r#") => self.raw_string()?,
+            _ => {
+                let s = self.bare_identifier()?;
+                let v = match s.as_str() {
+                    _ if starts_like_number(&s) => Value::Number(
+                        scanner::number(&s)
+                            .ok_or_else(|| self.error(&format!("invalid number `{}`", s)))?,
+                    ),
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ if self.s.peek() == Some('=') => return self.property(s),
+                    _ => return Err(self.error(&format!("unexpected identifier `{}`", s))),
+                };
+                return Ok(Entry::Argument(v));
+            }
+        };
+
+        if self.s.peek() == Some('=') {
+            self.property(key)
+        } else {
+            Ok(Entry::Argument(Value::String(key)))
+        }
+    }
+
+    fn property(&mut self, key: String) -> Result<Entry, KdlError> {
+        self.expect('=')?;
+        Ok(Entry::Property(key, self.value()?))
+    }
+
+    fn value(&mut self) -> Result<Value, KdlError> {
+        let ty = self.type_annotation()?;
+        let v = match self.entry()? {
+            Entry::Argument(v) => v,
+            Entry::Property(..) => return Err(self.error("expected a value")),
+        };
+        Ok(match ty {
+            Some(ty) => {
+                let mut m = Map::new();
+                m.insert(VALUE_TYPE_KEY.to_string(), Value::String(ty));
+                m.insert(VALUE_KEY.to_string(), v);
+                Value::Object(m)
+            }
+            None => v,
+        })
+    }
+
+    fn identifier(&mut self) -> Result<String, KdlError> {
+        match self.s.peek() {
+            Some('"') => self.string(),
+            _ if self.s.starts_with("r\"") || self.s.starts_with("r#") => self.raw_string(),
+            _ => self.bare_identifier(),
+        }
+    }
+
+    fn bare_identifier(&mut self) -> Result<String, KdlError> {
+        let s = self.s.take_while(is_identifier_char);
+        if s.is_empty() {
+            match self.s.peek() {
+                Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+                None => Err(self.error("unexpected end of input")),
+            }
+        } else {
+            Ok(s)
+        }
+    }
+
+    fn string(&mut self) -> Result<String, KdlError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.s.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape()?),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn raw_string(&mut self) -> Result<String, KdlError> {
+        self.expect('r')?;
+        let hashes = self.s.take_while(|c| c == '#');
+        self.expect('"')?;
+        let close = format!("\"{}", hashes);
+        let mut s = String::new();
+        while !self.s.starts_with(&close) {
+            match self.s.next() {
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated raw string")),
+            }
+        }
+        self.s.skip(close.len());
+        Ok(s)
+    }
+
+    fn escape(&mut self) -> Result<char, KdlError> {
+        let c = match self.s.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some(c @ ('\\' | '/' | '"')) => c,
+            Some('u') => {
+                self.expect('{')?;
+                let hex = self.s.take_while(|c| c != '}');
+                self.expect('}')?;
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            Some(c) => return Err(self.error(&format!("invalid escape `\\{}`", c))),
+            None => return Err(self.error("unterminated escape")),
+        };
+        Ok(c)
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    indent: usize,
+}
+
+impl Writer {
+    fn nodes(&mut self, v: &Value) -> Result<(), KdlError> {
+        match v {
+            Value::Array(nodes) => nodes.iter().try_for_each(|node| self.node(node)),
+            _ => Err(KdlError::Node(
+                "a document must be an array of nodes".to_string(),
+            )),
+        }
+    }
+
+    fn node(&mut self, v: &Value) -> Result<(), KdlError> {
+        let m = v
+            .as_object()
+            .ok_or_else(|| KdlError::Node(format!("a node must be an object, but got `{}`", v)))?;
+        if let Some(k) = m.keys().find(|k| {
+            ![
+                NAME_KEY,
+                TYPE_KEY,
+                ARGUMENTS_KEY,
+                PROPERTIES_KEY,
+                CHILDREN_KEY,
+            ]
+            .contains(&k.as_str())
+        }) {
+            return Err(KdlError::Node(format!("unknown node key `{}`", k)));
+        }
+
+        self.out.push_str(&"    ".repeat(self.indent));
+        if let Some(ty) = m.get(TYPE_KEY) {
+            self.type_annotation(ty)?;
+        }
+        match m.get(NAME_KEY) {
+            Some(Value::String(name)) => self.identifier(name),
+            _ => {
+                return Err(KdlError::Node(
+                    "a node must have a string `name`".to_string(),
+                ))
+            }
+        }
+
+        match m.get(ARGUMENTS_KEY) {
+            Some(Value::Array(arguments)) => {
+                for v in arguments {
+                    self.out.push(' ');
+                    self.value(v)?;
+                }
+            }
+            Some(v) => {
+                return Err(KdlError::Node(format!(
+                    "`arguments` must be an array, but got `{}`",
+                    v
+                )))
+            }
+            None => {}
+        }
+        match m.get(PROPERTIES_KEY) {
+            Some(Value::Object(properties)) => {
+                for (k, v) in properties {
+                    self.out.push(' ');
+                    self.identifier(k);
+                    self.out.push('=');
+                    self.value(v)?;
+                }
+            }
+            Some(v) => {
+                return Err(KdlError::Node(format!(
+                    "`properties` must be an object, but got `{}`",
+                    v
+                )))
+            }
+            None => {}
+        }
+        match m.get(CHILDREN_KEY) {
+            Some(Value::Array(children)) if children.is_empty() => self.out.push_str(" {}"),
+            Some(children @ Value::Array(_)) => {
+                self.out.push_str(" {\n");
+                self.indent += 1;
+                self.nodes(children)?;
+                self.indent -= 1;
+                self.out.push_str(&"    ".repeat(self.indent));
+                self.out.push('}');
+            }
+            Some(v) => {
+                return Err(KdlError::Node(format!(
+                    "`children` must be an array, but got `{}`",
+                    v
+                )))
+            }
+            None => {}
+        }
+        self.out.push('\n');
+        Ok(())
+    }
+
+    fn type_annotation(&mut self, ty: &Value) -> Result<(), KdlError> {
+        let ty = ty
+            .as_str()
+            .ok_or_else(|| KdlError::Node(format!("a type must be a string, but got `{}`", ty)))?;
+        self.out.push('(');
+        self.identifier(ty);
+        self.out.push(')');
+        Ok(())
+    }
+
+    fn value(&mut self, v: &Value) -> Result<(), KdlError> {
+        match v {
+            Value::Null => self.out.push_str("null"),
+            Value::Bool(b) => self.out.push_str(&b.to_string()),
+            Value::Number(n) => self.out.push_str(&n.to_string()),
+            Value::String(s) => self.string(s),
+            Value::Object(m) if m.len() == 2 && m.contains_key(VALUE_TYPE_KEY) => {
+                let value = m
+                    .get(VALUE_KEY)
+                    .filter(|v| !v.is_object() && !v.is_array())
+                    .ok_or_else(|| KdlError::Node(format!("`{}` is not a kdl value", v)))?;
+                self.type_annotation(&m[VALUE_TYPE_KEY])?;
+                self.value(value)?;
+            }
+            _ => return Err(KdlError::Node(format!("`{}` is not a kdl value", v))),
+        }
+        Ok(())
+    }
+
+    fn identifier(&mut self, s: &str) {
+        if is_identifier(s) {
+            self.out.push_str(s);
+        } else {
+            self.string(s);
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            match c {
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\\' => self.out.push_str("\\\\"),
+                '"' => self.out.push_str("\\\""),
+                c if c.is_control() => self.out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+}
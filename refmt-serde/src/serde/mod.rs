@@ -1,13 +1,63 @@
+use std::io::{self, Read, Write};
+
 use serde::{Deserialize, Serialize};
 
+pub mod bson;
+pub mod edn;
+mod extjson;
+pub mod hcl;
 pub mod json;
+pub mod json5;
+pub mod kdl;
+pub mod plist;
+pub mod ron;
+mod scanner;
+pub mod table;
 pub mod toml;
 pub mod yaml;
 
+/// An error of the reader/writer-based methods, which also fail on reading or writing.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError<E: std::error::Error + 'static> {
+    #[error("can't read or write.")]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Format(E),
+}
+
 pub trait Serde {
-    type Error: std::error::Error;
+    type Error: std::error::Error + 'static;
     type ValueType: Serialize;
 
-    fn serialize(&self, v: &Self::ValueType) -> Result<String, Self::Error>;
-    fn deserialize<T: for<'de> Deserialize<'de>>(&self, s: &str) -> Result<T, Self::Error>;
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error>;
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error>;
+
+    /// Writes the value into the writer.
+    ///
+    /// The default implementation writes the output of [`Serde::serialize`]. The formats which
+    /// have a serializer into writers write without buffering the output.
+    fn serialize_to<W: Write>(
+        &self,
+        v: &Self::ValueType,
+        mut w: W,
+    ) -> Result<(), StreamError<Self::Error>> {
+        let b = self.serialize(v).map_err(StreamError::Format)?;
+        w.write_all(&b)?;
+        Ok(())
+    }
+
+    /// Reads a value from the reader.
+    ///
+    /// The default implementation reads the whole input into memory, and passes it to
+    /// [`Serde::deserialize`]. The formats which have a deserializer from readers parse without
+    /// buffering the input, though the whole value is still built.
+    fn deserialize_from<R: Read, T: for<'de> Deserialize<'de>>(
+        &self,
+        mut r: R,
+    ) -> Result<T, StreamError<Self::Error>> {
+        let mut b = Vec::new();
+        r.read_to_end(&mut b)?;
+        self.deserialize(&b).map_err(StreamError::Format)
+    }
 }
@@ -0,0 +1,146 @@
+use std::io::Cursor;
+
+use ::plist::{Date, Dictionary, Integer, Uid, Value as PlistValue};
+use serde::Deserialize;
+use serde_json::{json, Map, Number, Value};
+
+use super::{extjson, Serde};
+
+const UID_KEY: &str = "$uid";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlistError {
+    #[error("can't serialize into/deserialize from plist.")]
+    Plist(#[from] ::plist::Error),
+
+    #[error("can't convert plist value.")]
+    Value(#[from] serde_json::Error),
+
+    #[error("can't read date. date:{_0}")]
+    Date(String),
+
+    #[error("can't read data.")]
+    Data(#[from] base64::DecodeError),
+
+    #[error("plist doesn't support null.")]
+    Null,
+
+    #[error("can't represent the real number. number:{_0}")]
+    Real(f64),
+
+    #[error("unsupported plist value. value:{_0}")]
+    Unsupported(String),
+}
+
+/// Apple property list in the XML encoding.
+///
+/// `date` and `data` are mapped to the same representations as BSON, `{"$date": "..."}` and
+/// `{"$binary": {...}}`, and `UID` of the binary encoding is mapped to `{"$uid": <integer>}`.
+pub struct Plist;
+
+/// Apple property list in the binary encoding. See [`Plist`] for the mapping of values.
+pub struct BinaryPlist;
+
+impl Serde for Plist {
+    type Error = PlistError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut b = Vec::new();
+        from_json(v)?.to_writer_xml(&mut b)?;
+        b.push(b'\n');
+        Ok(b)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        deserialize(b)
+    }
+}
+
+impl Serde for BinaryPlist {
+    type Error = PlistError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut b = Vec::new();
+        from_json(v)?.to_writer_binary(&mut b)?;
+        Ok(b)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        deserialize(b)
+    }
+}
+
+fn deserialize<T: for<'de> Deserialize<'de>>(b: &[u8]) -> Result<T, PlistError> {
+    // the encoding is detected by the reader.
+    let v = PlistValue::from_reader(Cursor::new(b))?;
+    Ok(serde_json::from_value(into_json(v)?)?)
+}
+
+fn into_json(v: PlistValue) -> Result<Value, PlistError> {
+    let v = match v {
+        PlistValue::Array(a) => {
+            Value::Array(a.into_iter().map(into_json).collect::<Result<_, _>>()?)
+        }
+        PlistValue::Dictionary(d) => Value::Object(
+            d.into_iter()
+                .map(|(k, v)| into_json(v).map(|v| (k, v)))
+                .collect::<Result<_, _>>()?,
+        ),
+        PlistValue::Boolean(b) => Value::Bool(b),
+        PlistValue::Data(b) => extjson::binary(&b),
+        PlistValue::Date(d) => extjson::date(d.to_xml_format()),
+        PlistValue::Real(f) => Value::Number(Number::from_f64(f).ok_or(PlistError::Real(f))?),
+        PlistValue::Integer(i) => match (i.as_signed(), i.as_unsigned()) {
+            (Some(i), _) => Value::from(i),
+            (None, Some(u)) => Value::from(u),
+            (None, None) => unreachable!("an integer is either signed or unsigned"),
+        },
+        PlistValue::String(s) => Value::String(s),
+        PlistValue::Uid(u) => json!({ UID_KEY: u.get() }),
+        // the values which are added to plist later.
+        v => return Err(PlistError::Unsupported(format!("{:?}", v))),
+    };
+    Ok(v)
+}
+
+fn from_json(v: &Value) -> Result<PlistValue, PlistError> {
+    let v = match v {
+        Value::Null => return Err(PlistError::Null),
+        Value::Bool(b) => PlistValue::Boolean(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64(), n.as_f64()) {
+            (Some(i), _, _) => PlistValue::Integer(Integer::from(i)),
+            (None, Some(u), _) => PlistValue::Integer(Integer::from(u)),
+            (None, None, Some(f)) => PlistValue::Real(f),
+            (None, None, None) => unreachable!("a number is either integer or real"),
+        },
+        Value::String(s) => PlistValue::String(s.clone()),
+        Value::Array(a) => PlistValue::Array(a.iter().map(from_json).collect::<Result<_, _>>()?),
+        Value::Object(m) => from_json_object(m)?,
+    };
+    Ok(v)
+}
+
+fn from_json_object(m: &Map<String, Value>) -> Result<PlistValue, PlistError> {
+    if let Some(s) = extjson::as_date(m) {
+        let d = Date::from_xml_format(s).map_err(|_| PlistError::Date(s.to_string()))?;
+        return Ok(PlistValue::Date(d));
+    }
+    if let Some(b) = extjson::as_binary(m) {
+        return Ok(PlistValue::Data(b?));
+    }
+    if let Some(u) = m
+        .get(UID_KEY)
+        .filter(|_| m.len() == 1)
+        .and_then(Value::as_u64)
+    {
+        return Ok(PlistValue::Uid(Uid::new(u)));
+    }
+
+    let d = m
+        .iter()
+        .map(|(k, v)| from_json(v).map(|v| (k.clone(), v)))
+        .collect::<Result<Dictionary, _>>()?;
+    Ok(PlistValue::Dictionary(d))
+}
@@ -0,0 +1,572 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::scanner::{self, Scanner};
+use super::Serde;
+
+const NAME_KEY: &str = "$name";
+const TUPLE_KEY: &str = "$tuple";
+const MAP_KEY: &str = "$map";
+const CHAR_KEY: &str = "$char";
+const BYTES_KEY: &str = "$bytes";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RonError {
+    #[error("can't parse ron. line:{line}, column:{column}, cause:{message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("can't convert ron value.")]
+    Value(#[from] serde_json::Error),
+
+    #[error("ron must be a utf-8 text.")]
+    Utf8(#[from] std::str::Utf8Error),
+
+    #[error("ron map entries must be pairs of a key and a value: {0}")]
+    MapEntry(Value),
+}
+
+/// RON, Rusty Object Notation.
+///
+/// RON values which JSON doesn't have are mapped to tagged objects, so that a RON document is
+/// written back as it was:
+///
+/// | RON                 | JSON                                          |
+/// |---------------------|-----------------------------------------------|
+/// | `(a: 1)`            | `{"a": 1}`                                    |
+/// | `Name(a: 1)`        | `{"$name": "Name", "a": 1}`                   |
+/// | `Name(1, 2)`        | `{"$name": "Name", "$tuple": [1, 2]}`         |
+/// | `Name`              | `{"$name": "Name"}`                           |
+/// | `(1, 2)`, `()`      | `{"$tuple": [1, 2]}`, `{"$tuple": []}`        |
+/// | `{"a": 1}`, `{}`    | `{"$map": {"a": 1}}`, `{"$map": {}}`          |
+/// | `{1: "a"}`          | `{"$map": [[1, "a"]]}`                        |
+/// | `'c'`               | `{"$char": "c"}`                              |
+/// | `b"ab"`, `br"ab"`   | `{"$bytes": [97, 98]}`                        |
+/// | `None`, `Some(1)`   | `null`, `{"$name": "Some", "$tuple": [1]}`    |
+///
+/// Struct names and enum variants share the `$name` tag, since RON doesn't tell them apart.
+/// Raw strings are read as strings, and byte literals like `b'a'` as numbers. Objects whose
+/// keys are not identifiers, including the tags, are written as maps.
+/// Comments and `#![enable(...)]` attributes are not kept.
+pub struct Ron;
+
+impl Serde for Ron {
+    type Error = RonError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let mut w = Writer::default();
+        w.value(v)?;
+        w.out.push('\n');
+        Ok(w.out.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let mut p = Parser::new(std::str::from_utf8(b)?);
+        p.attributes()?;
+        let v = p.value()?;
+        p.skip_whitespace();
+        if p.s.peek().is_some() {
+            return Err(p.error("trailing characters"));
+        }
+        Ok(serde_json::from_value(v)?)
+    }
+}
+
+fn tagged(key: &str, v: Value) -> Value {
+    let mut m = Map::new();
+    m.insert(key.to_string(), v);
+    Value::Object(m)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Returns true if the fields are written as `(a: 1)`, or `(1, 2)` of a tuple.
+fn is_struct(fields: &[(&String, &Value)]) -> bool {
+    match fields {
+        [(k, Value::Array(_))] if k.as_str() == TUPLE_KEY => true,
+        _ => fields.iter().all(|(k, _)| is_identifier(k)),
+    }
+}
+
+struct Parser<'a> {
+    s: Scanner<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Parser { s: Scanner::new(s) }
+    }
+
+    fn error(&self, message: &str) -> RonError {
+        RonError::Syntax {
+            line: self.s.line,
+            column: self.s.column,
+            message: message.to_string(),
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.s.next()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.s.peek()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), RonError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error(&format!("expected `{}`", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.s.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.next();
+                }
+                _ if self.s.starts_with("//") => while !matches!(self.next(), Some('\n') | None) {},
+                _ if self.s.starts_with("/*") => {
+                    self.s.skip(2);
+                    while !self.s.starts_with("*/") && self.next().is_some() {}
+                    self.s.skip(2);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Skips `#![enable(...)]` attributes.
+    fn attributes(&mut self) -> Result<(), RonError> {
+        while self.peek() == Some('#') {
+            while !matches!(self.next(), Some(']') | None) {}
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value, RonError> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('\'') => {
+                let c = self.char()?;
+                Ok(tagged(CHAR_KEY, Value::String(c.to_string())))
+            }
+            Some('r') if self.is_raw_string("r") => Ok(Value::String(self.raw_string()?)),
+            Some('b') if self.s.starts_with("b'") => {
+                self.next();
+                let c = self.char()?;
+                u8::try_from(c)
+                    .map(Value::from)
+                    .map_err(|_| self.error("byte literals must be ascii"))
+            }
+            Some('b') if self.s.starts_with("b\"") || self.is_raw_string("br") => {
+                self.next();
+                let bytes = self.byte_string()?;
+                Ok(tagged(BYTES_KEY, Value::from(bytes)))
+            }
+            Some('[') => {
+                self.next();
+                let values = self.sequence(']', Self::value)?;
+                Ok(Value::Array(values))
+            }
+            Some('{') => self.map(),
+            Some('(') => self.parenthesized(None),
+            Some(c) if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.named(),
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn sequence<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, RonError>,
+    ) -> Result<Vec<T>, RonError> {
+        let mut items = Vec::new();
+        loop {
+            if self.peek() == Some(close) {
+                self.next();
+                return Ok(items);
+            }
+            items.push(item(self)?);
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                }
+                Some(c) if c == close => {}
+                _ => return Err(self.error(&format!("expected `,` or `{}`", close))),
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Value, RonError> {
+        self.expect('{')?;
+        let entries = self.sequence('}', |p| {
+            let k = p.value()?;
+            p.expect(':')?;
+            Ok((k, p.value()?))
+        })?;
+
+        let v = if entries.iter().all(|(k, _)| k.is_string()) {
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.as_str().unwrap().to_string(), v))
+                    .collect(),
+            )
+        } else {
+            Value::Array(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| Value::Array(vec![k, v]))
+                    .collect(),
+            )
+        };
+        Ok(tagged(MAP_KEY, v))
+    }
+
+    /// Parses a struct `(a: 1)` or a tuple `(1, 2)`, which may follow a name.
+    fn parenthesized(&mut self, name: Option<String>) -> Result<Value, RonError> {
+        self.expect('(')?;
+        self.skip_whitespace();
+
+        let mut m = Map::new();
+        if let Some(name) = name {
+            m.insert(NAME_KEY.to_string(), Value::String(name));
+        }
+
+        if self.is_field() {
+            let fields = self.sequence(')', |p| {
+                let k = p.identifier();
+                p.expect(':')?;
+                Ok((k, p.value()?))
+            })?;
+            m.extend(fields);
+        } else {
+            let values = self.sequence(')', Self::value)?;
+            m.insert(TUPLE_KEY.to_string(), Value::Array(values));
+        }
+        Ok(Value::Object(m))
+    }
+
+    fn is_field(&mut self) -> bool {
+        let mut ahead = Parser { s: self.s.clone() };
+        !ahead.identifier().is_empty() && ahead.peek() == Some(':')
+    }
+
+    fn named(&mut self) -> Result<Value, RonError> {
+        let name = self.identifier();
+        match name.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "None" => return Ok(Value::Null),
+            "inf" | "NaN" => return Err(self.error("json doesn't support inf and NaN")),
+            _ => {}
+        }
+
+        if self.peek() == Some('(') {
+            self.parenthesized(Some(name))
+        } else {
+            Ok(tagged(NAME_KEY, Value::String(name)))
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        self.s.take_while(|c| c.is_alphanumeric() || c == '_')
+    }
+
+    fn number(&mut self) -> Result<Value, RonError> {
+        let s = self
+            .s
+            .take_while(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+        scanner::number(&s)
+            .map(Value::Number)
+            .ok_or_else(|| self.error(&format!("invalid number `{}`", s)))
+    }
+
+    fn char(&mut self) -> Result<char, RonError> {
+        self.expect('\'')?;
+        let c = match self.next() {
+            Some('\\') => self.escape()?,
+            Some(c) => c,
+            None => return Err(self.error("unterminated char")),
+        };
+        self.expect('\'')?;
+        Ok(c)
+    }
+
+    fn string(&mut self) -> Result<String, RonError> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape()?),
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Returns true if the input is `r"`, `r#"` and so on after the prefix.
+    fn is_raw_string(&self, prefix: &str) -> bool {
+        let mut ahead = self.s.clone();
+        ahead.skip(prefix.len());
+        ahead.take_while(|c| c == '#');
+        ahead.peek() == Some('"') && self.s.starts_with(prefix)
+    }
+
+    fn raw_string(&mut self) -> Result<String, RonError> {
+        self.expect('r')?;
+        let hashes = self.s.take_while(|c| c == '#');
+        self.expect('"')?;
+        let close = format!("\"{}", hashes);
+        let mut s = String::new();
+        while !self.s.starts_with(&close) {
+            match self.next() {
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated raw string")),
+            }
+        }
+        self.s.skip(close.len());
+        Ok(s)
+    }
+
+    /// Parses `"..."` or `r#"..."#` after `b`, whose `\x` escapes are bytes.
+    fn byte_string(&mut self) -> Result<Vec<u8>, RonError> {
+        if self.s.peek() == Some('r') {
+            return Ok(self.raw_string()?.into_bytes());
+        }
+        self.expect('"')?;
+        let mut bytes = Vec::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(bytes),
+                Some('\\') if self.s.peek() == Some('x') => {
+                    self.next();
+                    let hex: String = (0..2).filter_map(|_| self.next()).collect();
+                    let b = u8::from_str_radix(&hex, 16)
+                        .map_err(|_| self.error("invalid byte escape"))?;
+                    bytes.push(b);
+                }
+                Some('\\') => {
+                    let c = self.escape()?;
+                    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                Some(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, RonError> {
+        let c = match self.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('u') => {
+                self.expect('{')?;
+                let mut hex = String::new();
+                while let Some(c) = self.next().filter(|&c| c != '}') {
+                    hex.push(c);
+                }
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            Some('x') => {
+                let hex: String = (0..2).filter_map(|_| self.next()).collect();
+                u8::from_str_radix(&hex, 16)
+                    .map(char::from)
+                    .map_err(|_| self.error("invalid byte escape"))?
+            }
+            Some(c) => c,
+            None => return Err(self.error("unterminated escape")),
+        };
+        Ok(c)
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    out: String,
+    indent: usize,
+}
+
+impl Writer {
+    fn value(&mut self, v: &Value) -> Result<(), RonError> {
+        match v {
+            Value::Null => self.out.push_str("None"),
+            Value::Bool(b) => self.out.push_str(&b.to_string()),
+            Value::Number(n) => self.out.push_str(&n.to_string()),
+            Value::String(s) => self.string(s, '"'),
+            Value::Array(a) => self.sequence('[', ']', a, |w, v| w.value(v))?,
+            Value::Object(m) => self.object(m)?,
+        }
+        Ok(())
+    }
+
+    fn object(&mut self, m: &Map<String, Value>) -> Result<(), RonError> {
+        if let (1, Some(Value::String(s))) = (m.len(), m.get(CHAR_KEY)) {
+            if let (Some(c), 1) = (s.chars().next(), s.chars().count()) {
+                self.string(&c.to_string(), '\'');
+                return Ok(());
+            }
+        }
+        match (m.len(), m.get(MAP_KEY)) {
+            (1, Some(Value::Object(entries))) => {
+                let entries = entries.iter().collect::<Vec<_>>();
+                return self.sequence('{', '}', &entries, |w, (k, v)| {
+                    w.string(k, '"');
+                    w.out.push_str(": ");
+                    w.value(v)
+                });
+            }
+            (1, Some(Value::Array(entries))) => {
+                return self.sequence('{', '}', entries, |w, entry| match entry {
+                    Value::Array(kv) if kv.len() == 2 => {
+                        w.value(&kv[0])?;
+                        w.out.push_str(": ");
+                        w.value(&kv[1])
+                    }
+                    _ => Err(RonError::MapEntry(entry.clone())),
+                });
+            }
+            _ => {}
+        }
+
+        if let (1, Some(Value::Array(values))) = (m.len(), m.get(BYTES_KEY)) {
+            let bytes = values
+                .iter()
+                .map(|v| v.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect::<Option<Vec<_>>>();
+            if let Some(bytes) = bytes {
+                self.bytes(&bytes);
+                return Ok(());
+            }
+        }
+
+        // the name is written only if the rest are written as a struct or a tuple.
+        let unnamed = m
+            .iter()
+            .filter(|(k, _)| k.as_str() != NAME_KEY)
+            .collect::<Vec<_>>();
+        let name = m
+            .get(NAME_KEY)
+            .and_then(Value::as_str)
+            .filter(|s| is_identifier(s))
+            .filter(|_| is_struct(&unnamed));
+        if let Some(name) = name {
+            self.out.push_str(name);
+        }
+
+        let fields = match name {
+            Some(_) => unnamed,
+            None => m.iter().collect(),
+        };
+        match (fields.as_slice(), fields.first()) {
+            ([], _) if name.is_some() => Ok(()),
+            // `()` is an empty tuple, so an empty object is an empty map.
+            ([], _) => {
+                self.out.push_str("{}");
+                Ok(())
+            }
+            ([_], Some((k, Value::Array(values)))) if k.as_str() == TUPLE_KEY => {
+                self.sequence('(', ')', values, |w, v| w.value(v))
+            }
+            _ if is_struct(&fields) => self.sequence('(', ')', &fields, |w, (k, v)| {
+                w.out.push_str(k);
+                w.out.push_str(": ");
+                w.value(v)
+            }),
+            _ => self.sequence('{', '}', &fields, |w, (k, v)| {
+                w.string(k, '"');
+                w.out.push_str(": ");
+                w.value(v)
+            }),
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.out.push_str("b\"");
+        for &b in bytes {
+            match b {
+                b'\n' => self.out.push_str("\\n"),
+                b'\r' => self.out.push_str("\\r"),
+                b'\t' => self.out.push_str("\\t"),
+                b'"' | b'\\' => {
+                    self.out.push('\\');
+                    self.out.push(char::from(b));
+                }
+                b' '..=b'~' => self.out.push(char::from(b)),
+                b => self.out.push_str(&format!("\\x{:02x}", b)),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn sequence<T>(
+        &mut self,
+        open: char,
+        close: char,
+        items: &[T],
+        item: impl Fn(&mut Self, &T) -> Result<(), RonError>,
+    ) -> Result<(), RonError> {
+        self.out.push(open);
+        if !items.is_empty() {
+            self.indent += 1;
+            for v in items {
+                self.newline();
+                item(self, v)?;
+                self.out.push(',');
+            }
+            self.indent -= 1;
+            self.newline();
+        }
+        self.out.push(close);
+        Ok(())
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&"    ".repeat(self.indent));
+    }
+
+    fn string(&mut self, s: &str, quote: char) {
+        self.out.push(quote);
+        for c in s.chars() {
+            match c {
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                '\\' => self.out.push_str("\\\\"),
+                c if c == quote => {
+                    self.out.push('\\');
+                    self.out.push(c);
+                }
+                c if c.is_control() => self.out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push(quote);
+    }
+}
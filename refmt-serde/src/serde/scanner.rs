@@ -0,0 +1,86 @@
+//! A character scanner for the formats parsed by hand, which keeps the position for errors.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde_json::Number;
+
+#[derive(Clone)]
+pub struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Scanner {
+            chars: s.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    pub fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    /// Returns true if the rest of the input starts with `s`.
+    pub fn starts_with(&self, s: &str) -> bool {
+        let mut chars = self.chars.clone();
+        s.chars().all(|c| chars.next() == Some(c))
+    }
+
+    pub fn skip(&mut self, n: usize) {
+        for _ in 0..n {
+            self.next();
+        }
+    }
+
+    pub fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.peek().filter(|&c| f(c)) {
+            s.push(c);
+            self.next();
+        }
+        s
+    }
+}
+
+/// Parses a number with an optional sign, `0x`/`0o`/`0b` radix prefix and `_` separators.
+pub fn number(s: &str) -> Option<Number> {
+    let s = s.replace('_', "");
+    let (sign, digits) = match s.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, s.strip_prefix('+').unwrap_or(&s)),
+    };
+    let radix = match digits.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => None,
+    };
+
+    if let Some(radix) = radix {
+        i64::from_str_radix(&digits[2..], radix)
+            .ok()
+            .map(|i| Number::from(sign * i))
+    } else if let Ok(i) = s.parse::<i64>() {
+        Some(Number::from(i))
+    } else if let Ok(u) = s.parse::<u64>() {
+        Some(Number::from(u))
+    } else {
+        s.parse::<f64>().ok().and_then(Number::from_f64)
+    }
+}
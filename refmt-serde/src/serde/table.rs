@@ -0,0 +1,163 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::Serde;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TableError {
+    #[error("can't render as a table. it must be an array of objects or an object of objects.")]
+    NotRecords,
+
+    #[error("{0} is an output only format.")]
+    WriteOnly(&'static str),
+}
+
+/// A Markdown table, which is an output only format.
+///
+/// See [`Table`] for the shape of the value.
+pub struct MarkdownTable;
+
+impl Serde for MarkdownTable {
+    type Error = TableError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let table = Table::new(v)?;
+        let escape = |s: String| {
+            s.replace('\\', "\\\\")
+                .replace('|', "\\|")
+                .replace('\n', "<br>")
+        };
+        let header = table.header.into_iter().map(escape).collect::<Vec<_>>();
+        let rows = table
+            .rows
+            .into_iter()
+            .map(|row| row.into_iter().map(escape).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let widths = header
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([h.chars().count(), 3])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let line = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .zip(&widths)
+                .map(|(c, &w)| format!(" {}{} ", c, " ".repeat(w - c.chars().count())))
+                .collect::<Vec<_>>();
+            format!("|{}|\n", cells.join("|"))
+        };
+
+        let mut out = line(&header);
+        let separator = widths.iter().map(|&w| "-".repeat(w)).collect::<Vec<_>>();
+        out.push_str(&line(&separator));
+        for row in &rows {
+            out.push_str(&line(row));
+        }
+        Ok(out.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, _b: &[u8]) -> Result<T, Self::Error> {
+        Err(TableError::WriteOnly("markdown table"))
+    }
+}
+
+/// An HTML table, which is an output only format.
+///
+/// See [`Table`] for the shape of the value.
+pub struct HtmlTable;
+
+impl Serde for HtmlTable {
+    type Error = TableError;
+    type ValueType = Value;
+
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
+        let table = Table::new(v)?;
+        let escape = |s: &str| {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        };
+        let row = |tag: &str, cells: &[String]| {
+            let mut out = String::from("    <tr>\n");
+            for c in cells {
+                out.push_str(&format!("      <{tag}>{}</{tag}>\n", escape(c), tag = tag));
+            }
+            out.push_str("    </tr>\n");
+            out
+        };
+
+        let mut out = String::from("<table>\n  <thead>\n");
+        out.push_str(&row("th", &table.header));
+        out.push_str("  </thead>\n  <tbody>\n");
+        for r in &table.rows {
+            out.push_str(&row("td", r));
+        }
+        out.push_str("  </tbody>\n</table>\n");
+        Ok(out.into_bytes())
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, _b: &[u8]) -> Result<T, Self::Error> {
+        Err(TableError::WriteOnly("html table"))
+    }
+}
+
+/// Cells of a table built from an array of objects, or an object of objects.
+///
+/// Columns are the union of the keys in order of appearance. An object of objects has its keys
+/// in the first column, whose header is empty. Nested values are written as compact JSON.
+struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn new(v: &Value) -> Result<Table, TableError> {
+        let (keys, records) = match v {
+            Value::Array(a) => (
+                None,
+                a.iter().map(Value::as_object).collect::<Option<Vec<_>>>(),
+            ),
+            Value::Object(m) => (
+                Some(m.keys().cloned().collect::<Vec<_>>()),
+                m.values().map(Value::as_object).collect::<Option<Vec<_>>>(),
+            ),
+            _ => return Err(TableError::NotRecords),
+        };
+        let records = records.ok_or(TableError::NotRecords)?;
+
+        let mut columns = Vec::<&String>::new();
+        for k in records.iter().flat_map(|r| r.keys()) {
+            if !columns.contains(&k) {
+                columns.push(k);
+            }
+        }
+
+        let row = |r: &Map<String, Value>| columns.iter().map(|&k| cell(r.get(k))).collect();
+        let mut header = columns.iter().map(|&k| k.clone()).collect::<Vec<_>>();
+        let mut rows = records.iter().map(|r| row(r)).collect::<Vec<Vec<_>>>();
+        if let Some(keys) = keys {
+            header.insert(0, String::new());
+            for (row, k) in rows.iter_mut().zip(keys) {
+                row.insert(0, k);
+            }
+        }
+        Ok(Table { header, rows })
+    }
+}
+
+fn cell(v: Option<&Value>) -> String {
+    match v {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => v.to_string(),
+    }
+}
@@ -17,13 +17,13 @@ impl Serde for Toml {
     type Error = TomlError;
     type ValueType = toml::Value;
 
-    fn serialize(&self, v: &Self::ValueType) -> Result<String, Self::Error> {
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
         let s = toml::to_string(v)?;
-        Ok(s)
+        Ok(s.into_bytes())
     }
 
-    fn deserialize<T: for<'de> Deserialize<'de>>(&self, s: &str) -> Result<T, Self::Error> {
-        let v = toml::from_str(s)?;
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let v = toml::from_slice(b)?;
         Ok(v)
     }
 }
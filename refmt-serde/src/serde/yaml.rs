@@ -1,4 +1,6 @@
-use super::Serde;
+use std::io::{Read, Write};
+
+use super::{Serde, StreamError};
 use serde::Deserialize;
 
 pub struct Yaml;
@@ -7,13 +9,28 @@ impl Serde for Yaml {
     type Error = serde_yaml::Error;
     type ValueType = serde_yaml::Value;
 
-    fn serialize(&self, v: &Self::ValueType) -> Result<String, Self::Error> {
+    fn serialize(&self, v: &Self::ValueType) -> Result<Vec<u8>, Self::Error> {
         let s = serde_yaml::to_string(v)?;
-        Ok(s)
+        Ok(s.into_bytes())
     }
 
-    fn deserialize<T: for<'de> Deserialize<'de>>(&self, s: &str) -> Result<T, Self::Error> {
-        let v = serde_yaml::from_str(s)?;
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, b: &[u8]) -> Result<T, Self::Error> {
+        let v = serde_yaml::from_slice(b)?;
         Ok(v)
     }
+
+    fn serialize_to<W: Write>(
+        &self,
+        v: &Self::ValueType,
+        w: W,
+    ) -> Result<(), StreamError<Self::Error>> {
+        serde_yaml::to_writer(w, v).map_err(StreamError::Format)
+    }
+
+    fn deserialize_from<R: Read, T: for<'de> Deserialize<'de>>(
+        &self,
+        r: R,
+    ) -> Result<T, StreamError<Self::Error>> {
+        serde_yaml::from_reader(r).map_err(StreamError::Format)
+    }
 }
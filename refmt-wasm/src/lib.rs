@@ -0,0 +1,127 @@
+//! The JavaScript API of refmt, built for `wasm32-unknown-unknown` with wasm-bindgen.
+//!
+//! ```js
+//! import init, { convert } from "./pkg/refmt_wasm.js";
+//!
+//! await init();
+//! convert('{"a": [1, 2]}', "json", "yaml", { indent: 4 }); // "a:\n-   1\n-   2\n"
+//! ```
+
+use std::str::FromStr;
+
+use refmt::converter::Converter;
+use refmt::errors;
+use refmt::format::FileFormat;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+/// The options of the conversion, which are the same as the command line options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Options {
+    indent: Option<usize>,
+}
+
+impl Options {
+    fn from_js(options: JsValue) -> Result<Options, JsError> {
+        let options: Option<Options> = serde_wasm_bindgen::from_value(options)?;
+        Ok(options.unwrap_or_default())
+    }
+}
+
+/// Converts the text from the format `from` into the format `to`, e.g. `"json"` and `"yaml"`.
+///
+/// `options` may be omitted, or an object like `{ indent: 4 }`. Binary formats are converted by
+/// `convertBytes`.
+#[wasm_bindgen]
+pub fn convert(text: &str, from: &str, to: &str, options: JsValue) -> Result<String, JsError> {
+    let options = Options::from_js(options)?;
+    Ok(convert_text(text, from, to, &options)?)
+}
+
+/// Converts the bytes like `convert`, for binary formats such as BSON.
+#[wasm_bindgen(js_name = convertBytes)]
+pub fn convert_bytes(
+    bytes: &[u8],
+    from: &str,
+    to: &str,
+    options: JsValue,
+) -> Result<Vec<u8>, JsError> {
+    let options = Options::from_js(options)?;
+    Ok(convert_bytes_with(bytes, from, to, &options)?)
+}
+
+/// Returns the names of the formats.
+#[wasm_bindgen]
+pub fn formats() -> Vec<String> {
+    FileFormat::names().into_iter().map(String::from).collect()
+}
+
+fn convert_text(text: &str, from: &str, to: &str, options: &Options) -> Result<String, Error> {
+    let format = FileFormat::from_str(to)?;
+    if format.is_binary() {
+        return Err(Error::Binary(format.name()));
+    }
+    let bytes = convert_bytes_with(text.as_bytes(), from, to, options)?;
+    String::from_utf8(bytes).map_err(|_| Error::Binary(format.name()))
+}
+
+fn convert_bytes_with(
+    bytes: &[u8],
+    from: &str,
+    to: &str,
+    options: &Options,
+) -> Result<Vec<u8>, Error> {
+    let converter = Converter::new()
+        .from(FileFormat::from_str(from)?)
+        .to(FileFormat::from_str(to)?);
+    let converter = match options.indent {
+        Some(indent) => converter.indent(indent),
+        None => converter,
+    };
+
+    let mut out = Vec::new();
+    converter.convert(bytes, &mut out)?;
+    Ok(out)
+}
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error(transparent)]
+    Refmt(#[from] errors::Error),
+
+    #[error("{0} is a binary format, use convertBytes instead")]
+    Binary(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_text, Error, Options};
+
+    #[test]
+    fn convert() {
+        let options = Options::default();
+        let r = convert_text(r#"{"a": [1, 2]}"#, "json", "yaml", &options);
+        assert_eq!("a:\n- 1\n- 2\n", r.unwrap());
+
+        let options = Options { indent: Some(4) };
+        let r = convert_text("a: [1, 2]", "yml", "json", &options);
+        assert_eq!(
+            "{\n    \"a\": [\n        1,\n        2\n    ]\n}\n",
+            r.unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let options = Options::default();
+        let r = convert_text("{}", "json", "bson", &options);
+        assert!(matches!(r, Err(Error::Binary("bson"))));
+
+        let r = convert_text("{", "json", "yaml", &options);
+        assert!(matches!(r, Err(Error::Refmt(_))));
+
+        let r = convert_text("{}", "conf", "yaml", &options);
+        assert!(matches!(r, Err(Error::Refmt(_))));
+    }
+}
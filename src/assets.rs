@@ -1,5 +1,16 @@
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use syntect::dumps::{dump_to_file, from_dump_file};
 use syntect::highlighting::{Theme, ThemeSet};
-use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet, SyntaxSetBuilder};
+
+use crate::errors;
+use crate::registry::Registry;
 
 const DEFAULT_THEME: &str = "Monokai Extended";
 const JSON_THEME: &str = "Monokai JSON+";
@@ -7,6 +18,8 @@ const JSON_THEME: &str = "Monokai JSON+";
 pub struct HighlightAssets {
     pub syntax_set: SyntaxSet,
     pub theme_set: ThemeSet,
+    /// Used when the theme set has no themes at all.
+    fallback_theme: Theme,
 }
 
 impl HighlightAssets {
@@ -14,34 +27,322 @@ impl HighlightAssets {
         HighlightAssets {
             syntax_set,
             theme_set,
+            fallback_theme: Theme::default(),
         }
     }
 
-    pub fn get_syntax(&self, name: &str) -> &SyntaxReference {
-        self.syntax_set.find_syntax_by_extension(name).unwrap()
+    pub fn get_syntax(&self, extension: &str) -> Result<&SyntaxReference, errors::Error> {
+        self.syntax_set
+            .find_syntax_by_extension(extension)
+            .ok_or_else(|| errors::Error::SyntaxName(extension.to_string()))
+    }
+
+    /// Returns the syntax for the extension, or the plain text syntax if it is missing.
+    pub fn get_syntax_or_plain_text(
+        &self,
+        extension: &str,
+    ) -> Result<&SyntaxReference, errors::Error> {
+        self.get_syntax(extension).or_else(|e| {
+            warn!("{}. highlight as plain text", e);
+            self.syntax_set.find_syntax_by_name("Plain Text").ok_or(e)
+        })
     }
 
     pub fn syntaxes(&self) -> &[SyntaxReference] {
         self.syntax_set.syntaxes()
     }
 
+    /// Returns the default theme, or any theme if it is missing.
     pub fn get_default_theme(&self) -> &Theme {
-        self.get_theme(DEFAULT_THEME)
+        self.get_theme(DEFAULT_THEME).unwrap_or_else(|e| {
+            warn!("{}. use another theme instead", e);
+            self.theme_set
+                .themes
+                .values()
+                .next()
+                .unwrap_or(&self.fallback_theme)
+        })
     }
 
-    pub fn get_theme(&self, name: &str) -> &Theme {
-        &self.theme_set.themes[name]
+    pub fn get_theme(&self, name: &str) -> Result<&Theme, errors::Error> {
+        self.theme_set
+            .themes
+            .get(name)
+            .ok_or_else(|| errors::Error::ThemeName(name.to_string()))
+    }
+
+    /// Returns the theme, or the default theme if it is missing.
+    pub fn get_theme_or_default(&self, name: &str) -> &Theme {
+        self.get_theme(name).unwrap_or_else(|e| {
+            warn!("{}. use the default theme instead", e);
+            self.get_default_theme()
+        })
     }
 
     pub fn get_theme_for_syntax(&self, syntax: &SyntaxReference) -> &Theme {
-        self.get_theme(if syntax.name.to_ascii_lowercase() == "json" {
-            JSON_THEME
+        if syntax.name.eq_ignore_ascii_case("json") {
+            self.get_theme_or_default(JSON_THEME)
         } else {
-            DEFAULT_THEME
-        })
+            self.get_default_theme()
+        }
+    }
+
+    /// Checks that every text format has a syntax, and that the default themes exist.
+    pub fn verify(&self) -> Result<(), errors::Error> {
+        let bundled = Registry::bundled();
+        let missing_syntaxes = bundled
+            .entries()
+            .iter()
+            .filter(|e| !e.is_binary())
+            .filter(|e| self.get_syntax(e.syntax_extension()).is_err())
+            .map(|e| format!("syntax for {} (.{})", e.name(), e.syntax_extension()));
+        let missing_themes = [DEFAULT_THEME, JSON_THEME]
+            .into_iter()
+            .filter(|name| !self.has_theme(name))
+            .map(|name| format!("theme {:?}", name));
+
+        let missing = missing_syntaxes.chain(missing_themes).collect::<Vec<_>>();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(errors::Error::InvalidAssets(format!(
+                "missing {}",
+                missing.join(", ")
+            )))
+        }
+    }
+
+    pub fn has_theme(&self, name: &str) -> bool {
+        self.theme_set.themes.contains_key(name)
+    }
+
+    pub fn theme_names(&self) -> Vec<&str> {
+        self.theme_set.themes.keys().map(|k| k.as_str()).collect()
     }
 
     pub fn themes(&self) -> Vec<&Theme> {
-        self.theme_set.themes.iter().map(|(_, v)| v).collect()
+        self.theme_set.themes.values().collect()
+    }
+}
+
+pub struct AssetBuilder {
+    syntax_set_builder: SyntaxSetBuilder,
+    theme_set: ThemeSet,
+}
+
+impl AssetBuilder {
+    pub fn build_from_files(
+        syntaxes_dir: &Path,
+        themes_dir: &Path,
+    ) -> Result<HighlightAssets, errors::Error> {
+        let mut builder = AssetBuilder::default();
+        builder.syntax_set_builder.add_plain_text_syntax();
+        builder.add_from_folders(syntaxes_dir, themes_dir)?;
+        Ok(builder.build())
+    }
+
+    /// Adds syntaxes and themes to the existing assets. Missing directories are skipped.
+    pub fn extend(
+        assets: HighlightAssets,
+        syntaxes_dir: &Path,
+        themes_dir: &Path,
+    ) -> Result<HighlightAssets, errors::Error> {
+        let mut builder = AssetBuilder {
+            syntax_set_builder: assets.syntax_set.into_builder(),
+            theme_set: assets.theme_set,
+        };
+        builder.add_from_folders(syntaxes_dir, themes_dir)?;
+        Ok(builder.build())
+    }
+
+    pub fn save(assets: &HighlightAssets, assets_dir: &Path) -> Result<(), errors::Error> {
+        dump_to_file(&assets.syntax_set, assets_dir.join("syntaxes.bin")).map_err(|e| {
+            errors::Error::CreatingAssets(format!("Cannot create syntaxes.bin. error:{:?}", e))
+        })?;
+        dump_to_file(&assets.theme_set, assets_dir.join("themes.bin")).map_err(|e| {
+            errors::Error::CreatingAssets(format!("Cannot create themes.bin. error:{:?}", e))
+        })?;
+
+        Ok(())
+    }
+
+    fn add_from_folders(
+        &mut self,
+        syntaxes_dir: &Path,
+        themes_dir: &Path,
+    ) -> Result<(), errors::Error> {
+        if syntaxes_dir.is_dir() {
+            self.syntax_set_builder
+                .add_from_folder(syntaxes_dir, true)
+                .map_err(|e| {
+                    errors::Error::CreatingAssets(format!("Cannot create syntaxes. error:{:?}", e))
+                })?;
+        }
+        if themes_dir.is_dir() {
+            self.theme_set.add_from_folder(themes_dir).map_err(|e| {
+                errors::Error::CreatingAssets(format!("Cannot create themes. error:{:?}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn build(self) -> HighlightAssets {
+        HighlightAssets::new(self.syntax_set_builder.build(), self.theme_set)
+    }
+}
+
+impl Default for AssetBuilder {
+    fn default() -> Self {
+        AssetBuilder {
+            syntax_set_builder: SyntaxSetBuilder::new(),
+            theme_set: ThemeSet::default(),
+        }
+    }
+}
+
+/// Directories of the user's syntaxes and themes, and the cache of the assets built with them.
+pub struct UserAssetDirs {
+    pub config_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl UserAssetDirs {
+    /// Returns `$XDG_CONFIG_HOME/refmt` and `$XDG_CACHE_HOME/refmt`, which default to
+    /// `~/.config/refmt` and `~/.cache/refmt`.
+    pub fn from_env() -> Option<UserAssetDirs> {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let xdg_dir = |name: &str, default: &str| {
+            env::var_os(name)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .or_else(|| home.as_ref().map(|h| h.join(default)))
+                .map(|p| p.join("refmt"))
+        };
+
+        Some(UserAssetDirs {
+            config_dir: xdg_dir("XDG_CONFIG_HOME", ".config")?,
+            cache_dir: xdg_dir("XDG_CACHE_HOME", ".cache")?,
+        })
+    }
+
+    pub fn syntaxes_dir(&self) -> PathBuf {
+        self.config_dir.join("syntaxes")
+    }
+
+    pub fn themes_dir(&self) -> PathBuf {
+        self.config_dir.join("themes")
+    }
+
+    /// Adds the user's syntaxes and themes to the assets, using the cache if it is up to date.
+    pub fn load(&self, assets: HighlightAssets) -> Result<HighlightAssets, errors::Error> {
+        let fingerprint = match self.fingerprint() {
+            Some(fingerprint) => fingerprint,
+            None => return Ok(assets),
+        };
+
+        if let Some(cached) = self.load_cache(&fingerprint) {
+            debug!("load assets from cache: {:?}", self.cache_dir);
+            return Ok(cached);
+        }
+
+        debug!("build assets from: {:?}", self.config_dir);
+        let assets = AssetBuilder::extend(assets, &self.syntaxes_dir(), &self.themes_dir())?;
+        if let Err(e) = self.save_cache(&assets, &fingerprint) {
+            warn!("cannot save assets cache. error:{}", e);
+        }
+        Ok(assets)
+    }
+
+    fn load_cache(&self, fingerprint: &str) -> Option<HighlightAssets> {
+        let metadata = fs::read_to_string(self.cache_dir.join("metadata")).ok()?;
+        if metadata != fingerprint {
+            return None;
+        }
+        Some(HighlightAssets::new(
+            from_dump_file(self.cache_dir.join("syntaxes.bin")).ok()?,
+            from_dump_file(self.cache_dir.join("themes.bin")).ok()?,
+        ))
+    }
+
+    fn save_cache(&self, assets: &HighlightAssets, fingerprint: &str) -> Result<(), errors::Error> {
+        fs::create_dir_all(&self.cache_dir)?;
+        AssetBuilder::save(assets, &self.cache_dir)?;
+        fs::write(self.cache_dir.join("metadata"), fingerprint)?;
+        Ok(())
+    }
+
+    /// Identifies the user's files and the refmt version, or returns None if there are no files.
+    fn fingerprint(&self) -> Option<String> {
+        let mut files = Vec::new();
+        collect_files(&self.syntaxes_dir(), &mut files);
+        collect_files(&self.themes_dir(), &mut files);
+        if files.is_empty() {
+            return None;
+        }
+
+        files.sort();
+        let mut hasher = DefaultHasher::new();
+        files.hash(&mut hasher);
+        Some(format!(
+            "{}-{:016x}",
+            env!("CARGO_PKG_VERSION"),
+            hasher.finish()
+        ))
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<(PathBuf, u64, Option<std::time::SystemTime>)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(m) if m.is_dir() => collect_files(&path, files),
+            Ok(m) => files.push((path, m.len(), m.modified().ok())),
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSetBuilder;
+
+    use super::HighlightAssets;
+    use crate::errors;
+
+    fn assets() -> HighlightAssets {
+        let mut builder = SyntaxSetBuilder::new();
+        builder.add_plain_text_syntax();
+        HighlightAssets::new(builder.build(), ThemeSet::default())
+    }
+
+    #[test]
+    fn missing_syntax() {
+        let assets = assets();
+        assert!(matches!(
+            assets.get_syntax("json"),
+            Err(errors::Error::SyntaxName(ext)) if ext == "json"
+        ));
+        assert_eq!(
+            "Plain Text",
+            assets.get_syntax_or_plain_text("json").unwrap().name
+        );
+    }
+
+    #[test]
+    fn missing_theme() {
+        let assets = assets();
+        assert!(matches!(
+            assets.get_theme("Monokai Extended"),
+            Err(errors::Error::ThemeName(_))
+        ));
+        assert!(assets
+            .get_theme_or_default("Monokai Extended")
+            .name
+            .is_none());
     }
 }
@@ -1,65 +1,16 @@
+use std::collections::BTreeSet;
 use std::path::Path;
 
-use anyhow::{anyhow, Context};
 use clap::{App, Arg};
-use syntect::dumps::dump_to_file;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
+use syntect::dumps::from_dump_file;
 
-use refmt::assets::HighlightAssets;
-
-struct AssetBuilder {}
-
-impl AssetBuilder {
-    pub fn build_from_files(
-        syntaxes_dir: &Path,
-        themes_dir: &Path,
-    ) -> Result<HighlightAssets, anyhow::Error> {
-        let builder = AssetBuilder::default();
-        Ok(HighlightAssets {
-            syntax_set: builder.build_syntaxes(syntaxes_dir)?,
-            theme_set: builder.build_themes(themes_dir)?,
-        })
-    }
-
-    pub fn save(assets: HighlightAssets, assets_dir: &Path) -> Result<(), anyhow::Error> {
-        dump_to_file(&assets.syntax_set, assets_dir.join("syntaxes.bin"))
-            .context("Cannot create assets sytaxes.bin")?;
-        dump_to_file(&assets.theme_set, assets_dir.join("themes.bin"))
-            .context("Cannot create assets theme.bin")?;
-
-        Ok(())
-    }
-
-    fn build_syntaxes(&self, syntaxes_dir: &Path) -> Result<SyntaxSet, anyhow::Error> {
-        let mut syntax_set_builder = SyntaxSetBuilder::new();
-        syntax_set_builder.add_plain_text_syntax();
-        syntax_set_builder
-            .add_from_folder(syntaxes_dir, true)
-            .map_err(|e| anyhow!("Cannot create syntaxes. error:{:?}", e))?;
-
-        Ok(syntax_set_builder.build())
-    }
-
-    fn build_themes(&self, themes_dir: &Path) -> Result<ThemeSet, anyhow::Error> {
-        let mut theme_set = ThemeSet::default();
-        theme_set
-            .add_from_folder(themes_dir)
-            .map_err(|e| anyhow!("Cannot create themes. error:{:?}", e))?;
-
-        Ok(theme_set)
-    }
-}
-
-impl Default for AssetBuilder {
-    fn default() -> Self {
-        AssetBuilder {}
-    }
-}
+use refmt::assets::{AssetBuilder, HighlightAssets};
+use refmt::errors;
 
 #[derive(Debug)]
 struct ProgramOption {
     assets_dir: String,
+    verify: bool,
 }
 
 fn parse_args() -> ProgramOption {
@@ -72,18 +23,61 @@ fn parse_args() -> ProgramOption {
                 .value_name("ASSETS_DIR")
                 .required(true),
         )
+        .arg(
+            Arg::with_name("VERIFY")
+                .long("verify")
+                .help("Verify the bundled assets instead of generating them"),
+        )
         .get_matches();
 
     ProgramOption {
         assets_dir: m.value_of("ASSETS_DIR").unwrap().to_string(),
+        verify: m.is_present("VERIFY"),
+    }
+}
+
+fn names(assets: &HighlightAssets) -> (BTreeSet<String>, BTreeSet<String>) {
+    (
+        assets.syntaxes().iter().map(|s| s.name.clone()).collect(),
+        assets.theme_names().into_iter().map(String::from).collect(),
+    )
+}
+
+/// Checks the dumps in the assets directory, and that they are up to date with the sources if
+/// the submodules are checked out.
+fn verify(assets_dir: &Path) -> Result<(), errors::Error> {
+    let load_error = |e| errors::Error::InvalidAssets(format!("Cannot load dumps. error:{:?}", e));
+    let bundled = HighlightAssets::new(
+        from_dump_file(assets_dir.join("syntaxes.bin")).map_err(load_error)?,
+        from_dump_file(assets_dir.join("themes.bin")).map_err(load_error)?,
+    );
+    bundled.verify()?;
+
+    let sources =
+        AssetBuilder::build_from_files(&assets_dir.join("syntaxes"), &assets_dir.join("themes"))?;
+    if sources.theme_names().is_empty() {
+        eprintln!("sources are not checked out. skip comparing the dumps with them.");
+        return Ok(());
+    }
+    if names(&bundled) != names(&sources) {
+        return Err(errors::Error::InvalidAssets(
+            "the dumps are out of date. run assets/create.bash".to_string(),
+        ));
     }
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let options = parse_args();
     let assets_dir = Path::new(&options.assets_dir);
+    if options.verify {
+        verify(assets_dir)?;
+        return Ok(());
+    }
+
     let assets =
         AssetBuilder::build_from_files(&assets_dir.join("syntaxes"), &assets_dir.join("themes"))?;
-    AssetBuilder::save(assets, Path::new(&options.assets_dir))?;
+    assets.verify()?;
+    AssetBuilder::save(&assets, Path::new(&options.assets_dir))?;
     Ok(())
 }
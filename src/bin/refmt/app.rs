@@ -1,17 +1,35 @@
-use std::fs::File;
-use std::io::{stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, Stdin, Write};
 use std::path::Path;
+use std::process;
 use std::str::FromStr;
 
-use clap::{crate_authors, crate_name, crate_version, App as ClapApp, AppSettings, Arg};
+#[cfg(feature = "highlight")]
+use std::cell::OnceCell;
+
+#[cfg(feature = "highlight")]
+use ansi_term::Style;
+use clap::{
+    crate_authors, crate_name, crate_version, App as ClapApp, AppSettings, Arg, ArgGroup,
+    ArgMatches,
+};
 use log::debug;
+#[cfg(feature = "highlight")]
 use syntect::dumps::from_binary;
 
-use refmt::assets::HighlightAssets;
+#[cfg(feature = "highlight")]
+use refmt::assets::{HighlightAssets, UserAssetDirs};
+use refmt::converter::{Converter, Flattening, IndexStyle, Interpolation, KeyStyle};
 use refmt::errors;
-use refmt::format::{FileFormat, FormattedText};
+use refmt::format::{infer_format, infer_format_name, FileFormat, FormattedText};
+#[cfg(feature = "highlight")]
+use refmt::printer::{
+    ColorDepth, Decorations, HighlightTextPrinter, HtmlPrinter, HtmlStyle, SvgPrinter,
+};
+use refmt::printer::{PlainTextPrinter, Printer};
 
-use crate::printer::{HighlightTextPrinter, PlainTextPrinter, Printer};
+use crate::pager::{Pager, Paging};
 
 #[derive(Debug)]
 struct Config {
@@ -19,41 +37,99 @@ struct Config {
     input_format: FileFormat,
     output_file: Option<String>,
     output_format: FileFormat,
+    indent: Option<usize>,
+    interpolation: Option<Interpolation>,
+    flattening: Option<Flattening>,
+    paging: Paging,
+    #[cfg(feature = "highlight")]
+    highlight: HighlightConfig,
+}
+
+/// Settings of the highlighted output.
+#[cfg(feature = "highlight")]
+#[derive(Debug)]
+struct HighlightConfig {
     color_enabled: bool,
-    theme_name: String,
+    color_depth: ColorDepth,
+    theme_name: Option<String>,
+    export: Option<Export>,
+    decorations: Decorations,
 }
 
-fn infer_format_name<'a>(file: Option<&'a str>, format_name: Option<&'a str>) -> Option<&'a str> {
-    if let Some(format_name) = format_name {
-        Some(format_name)
-    } else if let Some(file) = file {
-        Path::new(file).extension().and_then(|ext| ext.to_str())
-    } else {
-        None
+#[cfg(feature = "highlight")]
+impl HighlightConfig {
+    fn new(matches: &ArgMatches, title: &str) -> Self {
+        let color_enabled = is_color_enabled(matches.value_of("COLOR").unwrap_or("auto"));
+        let color_depth = color_depth(matches);
+        debug!(
+            "color_enabled: {}, color_depth: {:?}",
+            color_enabled, color_depth
+        );
+
+        let theme_name = matches.value_of("THEME");
+        debug!("theme_name: {:?}", theme_name);
+
+        let export = matches.value_of("EXPORT").and_then(Export::from_name);
+        debug!("export: {:?}", export);
+
+        let decorations =
+            Decorations::from_names(matches.values_of("STYLE").unwrap_or_default(), title);
+        debug!("decorations: {:?}", decorations);
+
+        HighlightConfig {
+            color_enabled,
+            color_depth,
+            theme_name: theme_name.map(|s| s.to_string()),
+            export,
+            decorations,
+        }
     }
 }
 
-fn infer_format(
-    file: Option<&str>,
-    format_name: Option<&str>,
-) -> Result<FileFormat, errors::Error> {
-    let format_name = infer_format_name(file, format_name);
-    if let Some(format_name) = format_name {
-        FileFormat::from_str(format_name)
-    } else {
-        Err(errors::Error::InferFormat)
+/// Returns the color depth of the option, or detects it by the terminal.
+#[cfg(feature = "highlight")]
+fn color_depth(matches: &ArgMatches) -> ColorDepth {
+    matches
+        .value_of("COLOR_DEPTH")
+        .and_then(ColorDepth::from_name)
+        .unwrap_or_else(ColorDepth::from_env)
+}
+
+/// A document format to write the highlighted output in, instead of the terminal.
+#[cfg(feature = "highlight")]
+#[derive(Copy, Clone, Debug)]
+enum Export {
+    Html(HtmlStyle),
+    Svg,
+}
+
+#[cfg(feature = "highlight")]
+impl Export {
+    fn names() -> &'static [&'static str] {
+        &["html", "html-classes", "svg"]
+    }
+
+    fn from_name(name: &str) -> Option<Export> {
+        match name {
+            "html" => Some(Export::Html(HtmlStyle::Inline)),
+            "html-classes" => Some(Export::Html(HtmlStyle::Classes)),
+            "svg" => Some(Export::Svg),
+            _ => None,
+        }
     }
 }
 
 impl Config {
-    fn new(app: ClapApp, color_enabled: bool) -> Result<Self, errors::Error> {
-        let matches = app.get_matches();
+    fn new(matches: &ArgMatches) -> Result<Self, errors::Error> {
         let input_file = matches.value_of("INPUT_FILE");
         debug!("input_file: {:?}", input_file);
 
         let input_format = matches.value_of("INPUT_FORMAT");
         debug!("input_format: {:?}", input_format);
         let input_format = infer_format(input_file, input_format)?;
+        if input_format.is_write_only() {
+            return Err(errors::Error::WriteOnlyFormat(input_format.name()));
+        }
 
         let output_file = matches.value_of("OUTPUT_FILE");
         debug!("output_file: {:?}", output_file);
@@ -64,18 +140,79 @@ impl Config {
             .map(FileFormat::from_str)
             .unwrap_or_else(|| Ok(input_format))?;
 
-        let theme_name = "Monokai Extended";
+        let indent = matches.value_of("INDENT").and_then(|s| s.parse().ok());
+        debug!("indent: {:?}", indent);
+
+        let interpolation = matches.is_present("INTERPOLATE").then(|| {
+            Interpolation::new()
+                .strict(matches.is_present("STRICT"))
+                .files(matches.is_present("INTERPOLATE_FILES"))
+        });
+        debug!("interpolation: {:?}", interpolation);
+
+        let flattening = flattening(matches);
+        debug!("flattening: {:?}", flattening);
+
+        let paging = matches
+            .value_of("PAGING")
+            .and_then(Paging::from_name)
+            .unwrap_or(Paging::Auto);
+        debug!("paging: {:?}", paging);
+
+        #[cfg(feature = "highlight")]
+        let highlight = HighlightConfig::new(
+            matches,
+            &format!(
+                "{} → {}",
+                input_file.unwrap_or("STDIN"),
+                output_format.name()
+            ),
+        );
+
         Ok(Config {
             input_file: input_file.map(|s| s.to_string()),
             input_format,
             output_file: output_file.map(|s| s.to_string()),
             output_format,
-            color_enabled: color_enabled,
-            theme_name: theme_name.to_string(),
+            indent,
+            interpolation,
+            flattening,
+            paging,
+            #[cfg(feature = "highlight")]
+            highlight,
         })
     }
 }
 
+/// Returns whether to flatten or unflatten the keys, with the style of the keys.
+fn flattening(matches: &ArgMatches) -> Option<Flattening> {
+    let index_style = match matches.value_of("INDEX_STYLE") {
+        Some("bracket") => IndexStyle::Bracket,
+        _ => IndexStyle::Dot,
+    };
+    let style = KeyStyle::new(matches.value_of("SEPARATOR").unwrap_or("."), index_style);
+
+    if matches.is_present("FLATTEN") {
+        Some(Flattening::Flatten(style))
+    } else if matches.is_present("UNFLATTEN") {
+        Some(Flattening::Unflatten(style))
+    } else {
+        None
+    }
+}
+
+/// Decides whether to use colors, following https://no-color.org and https://bixense.com/clicolors.
+fn is_color_enabled(when: &str) -> bool {
+    let is_set = |name: &str| env::var_os(name).is_some_and(|v| !v.is_empty() && v != "0");
+    match when {
+        "always" => true,
+        "never" => false,
+        _ if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) => false,
+        _ if is_set("CLICOLOR_FORCE") => true,
+        _ => atty::is(atty::Stream::Stdout),
+    }
+}
+
 fn build_clap_app(color_enabled: bool) -> clap::App<'static, 'static> {
     let color_setting = if color_enabled {
         AppSettings::ColoredHelp
@@ -83,8 +220,8 @@ fn build_clap_app(color_enabled: bool) -> clap::App<'static, 'static> {
         AppSettings::ColorNever
     };
 
-    ClapApp::new(crate_name!())
-        .about("reformat between JSON, YAML and TOML.")
+    let app = ClapApp::new(crate_name!())
+        .about("reformat between JSON, JSON5, YAML, TOML, HCL, RON, KDL, EDN, BSON and plist.")
         .author(crate_authors!())
         .version(crate_version!())
         .global_setting(color_setting)
@@ -103,7 +240,7 @@ fn build_clap_app(color_enabled: bool) -> clap::App<'static, 'static> {
                 .takes_value(true)
                 .value_name("FORMAT_NAME")
                 .case_insensitive(true)
-                .possible_values(&FileFormat::names()),
+                .possible_values(&FileFormat::readable_names()),
         )
         .arg(
             Arg::with_name("OUTPUT_FILE")
@@ -122,20 +259,277 @@ fn build_clap_app(color_enabled: bool) -> clap::App<'static, 'static> {
                 .case_insensitive(true)
                 .possible_values(&FileFormat::names()),
         )
+        .arg(
+            Arg::with_name("INDENT")
+                .help("set the number of spaces for indentation of JSON, JSON5, JSONC, YAML, KDL and RON")
+                .long("indent")
+                .takes_value(true)
+                .value_name("WIDTH")
+                .validator(|s| s.parse::<usize>().map(|_| ()).map_err(|e| e.to_string())),
+        )
+        .arg(
+            Arg::with_name("INTERPOLATE")
+                .help("expand ${VAR} and ${VAR:-default} in string values. Environment variables such as secrets may be written into the output, so don't use it for untrusted input")
+                .long("interpolate"),
+        )
+        .arg(
+            Arg::with_name("INTERPOLATE_FILES")
+                .help("also expand ${file:path} of --interpolate into the content of the file. Any file readable by you can be read into the output, so use it only for trusted input")
+                .long("interpolate-files")
+                .requires("INTERPOLATE"),
+        )
+        .arg(
+            Arg::with_name("STRICT")
+                .help("fail on undefined variables of --interpolate, instead of expanding them to empty strings")
+                .long("strict")
+                .requires("INTERPOLATE"),
+        )
+        .arg(
+            Arg::with_name("FLATTEN")
+                .help("flatten nested objects and arrays into keys like `a.b.0`, for key-value stores")
+                .long("flatten"),
+        )
+        .arg(
+            Arg::with_name("UNFLATTEN")
+                .help("unflatten keys like `a.b.0` into nested objects and arrays")
+                .long("unflatten"),
+        )
+        .group(ArgGroup::with_name("FLATTENING").args(&["FLATTEN", "UNFLATTEN"]))
+        .arg(
+            Arg::with_name("SEPARATOR")
+                .help("set the separator of the keys of --flatten and --unflatten. [default: .]")
+                .long("separator")
+                .takes_value(true)
+                .value_name("SEPARATOR")
+                .validator(|s| {
+                    if s.is_empty() {
+                        Err("the separator must not be empty".to_string())
+                    } else {
+                        Ok(())
+                    }
+                })
+                .requires("FLATTENING"),
+        )
+        .arg(
+            Arg::with_name("INDEX_STYLE")
+                .help("set how array indices are written in the keys of --flatten and --unflatten. `dot` is `a.0`, and `bracket` is `a[0]` [default: dot]")
+                .long("index-style")
+                .takes_value(true)
+                .value_name("STYLE")
+                .possible_values(&["dot", "bracket"])
+                .requires("FLATTENING"),
+        )
+        .arg(
+            Arg::with_name("PAGING")
+                .help("set when to page the output to a terminal with $PAGER, or `less -R`")
+                .long("paging")
+                .takes_value(true)
+                .value_name("WHEN")
+                .possible_values(Paging::names())
+                .default_value("auto"),
+        );
+
+    #[cfg(feature = "highlight")]
+    let app = highlight_args(app);
+    app
+}
+
+#[cfg(feature = "highlight")]
+fn highlight_args(app: clap::App<'static, 'static>) -> clap::App<'static, 'static> {
+    app.arg(
+            Arg::with_name("COLOR")
+                .help("set when to use colors. `auto` honors NO_COLOR and CLICOLOR_FORCE")
+                .long("color")
+                .takes_value(true)
+                .value_name("WHEN")
+                .possible_values(&["auto", "always", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::with_name("THEME")
+                .help("set the theme for syntax highlighting. Assume a theme for the output format if omitted")
+                .long("theme")
+                .takes_value(true)
+                .value_name("THEME_NAME")
+                .env("REFMT_THEME"),
+        )
+        .arg(
+            Arg::with_name("COLOR_DEPTH")
+                .help("set the number of colors of the terminal. Detected by COLORTERM and TERM if omitted")
+                .long("color-depth")
+                .takes_value(true)
+                .value_name("COLORS")
+                .possible_values(ColorDepth::names())
+                .env("REFMT_COLOR_DEPTH"),
+        )
+        .arg(
+            Arg::with_name("STYLE")
+                .help("set the decorations of the output to a terminal. `pointers` annotates each line of JSON or YAML with the JSON pointer of its value")
+                .long("style")
+                .takes_value(true)
+                .value_name("COMPONENTS")
+                .use_delimiter(true)
+                .possible_values(Decorations::names())
+                .default_value("plain"),
+        )
+        .arg(
+            Arg::with_name("EXPORT")
+                .help("write the highlighted output as a standalone HTML or SVG document")
+                .long("export")
+                .takes_value(true)
+                .value_name("DOCUMENT")
+                .possible_values(Export::names()),
+        )
+        .arg(
+            Arg::with_name("LIST_THEMES")
+                .help("print the available themes with a preview")
+                .long("list-themes"),
+        )
+}
+
+/// A sample text to preview the themes.
+#[cfg(feature = "highlight")]
+const THEME_PREVIEW: &str = r#"# config
+id: 123
+title: "Lorem ipsum dolor sit amet"
+enabled: true
+tags: [json, yaml]
+"#;
+
+enum Command {
+    Convert(Box<Config>),
+    #[cfg(feature = "highlight")]
+    ListThemes {
+        color_enabled: bool,
+        color_depth: ColorDepth,
+    },
 }
 
 pub struct App {
-    config: Config,
-    assets: HighlightAssets,
+    command: Command,
+    /// Loaded on the first use, since most conversions into files and pipes don't highlight.
+    #[cfg(feature = "highlight")]
+    assets: OnceCell<HighlightAssets>,
 }
 
 impl App {
     pub fn new() -> Result<App, errors::Error> {
-        let color_enabled = atty::is(atty::Stream::Stdout);
-        let config = Config::new(build_clap_app(color_enabled), color_enabled)?;
-        let assets = App::load_integrated_assets();
+        let matches = build_clap_app(is_color_enabled("auto")).get_matches();
 
+        #[cfg(feature = "highlight")]
+        if matches.is_present("LIST_THEMES") {
+            return Ok(App {
+                command: Command::ListThemes {
+                    color_enabled: is_color_enabled(matches.value_of("COLOR").unwrap_or("auto")),
+                    color_depth: color_depth(&matches),
+                },
+                assets: OnceCell::new(),
+            });
+        }
+
+        let config = Config::new(&matches)?;
         debug!("config: {:?}", config);
+        let app = App {
+            command: Command::Convert(Box::new(config)),
+            #[cfg(feature = "highlight")]
+            assets: OnceCell::new(),
+        };
+
+        // the theme is validated even if the output is not highlighted, e.g. into a file.
+        #[cfg(feature = "highlight")]
+        if let Command::Convert(config) = &app.command {
+            if let Some(name) = config.highlight.theme_name.as_deref() {
+                app.assets(Some(name))?;
+            }
+        }
+        Ok(app)
+    }
+
+    pub fn run(&self) -> Result<(), errors::Error> {
+        match &self.command {
+            Command::Convert(config) => {
+                let converter = Converter::new()
+                    .from(config.input_format)
+                    .to(config.output_format);
+                let converter = match config.interpolation {
+                    Some(interpolation) => converter.interpolate(interpolation),
+                    None => converter,
+                };
+                let converter = match config.flattening.as_ref() {
+                    Some(Flattening::Flatten(style)) => converter.flatten(style.clone()),
+                    Some(Flattening::Unflatten(style)) => converter.unflatten(style.clone()),
+                    None => converter,
+                };
+                if self.streams(config) {
+                    return self.stream(config, &converter);
+                }
+
+                let input_text = self.read_from_input(config)?;
+                let converter = match config.indent {
+                    Some(indent) => converter.indent(indent),
+                    None => converter,
+                };
+                let output_text = converter.convert_text(&input_text)?;
+
+                self.write_to_output(config, &output_text)
+            }
+            #[cfg(feature = "highlight")]
+            Command::ListThemes {
+                color_enabled,
+                color_depth,
+            } => self.list_themes(*color_enabled, *color_depth),
+        }
+    }
+
+    #[cfg(feature = "highlight")]
+    fn list_themes(
+        &self,
+        color_enabled: bool,
+        color_depth: ColorDepth,
+    ) -> Result<(), errors::Error> {
+        let stdout = stdout();
+        let mut w = stdout.lock();
+        let preview = FormattedText::new(FileFormat::Yaml, THEME_PREVIEW.to_string());
+        let assets = self.assets(None)?;
+        for name in assets.theme_names() {
+            if color_enabled {
+                writeln!(w, "Theme: {}\n", Style::new().bold().paint(name))?;
+                HighlightTextPrinter::new(assets, Some(name), color_depth, Decorations::default())
+                    .print(&mut w, &preview)?;
+                writeln!(w, "\x1b[0m")?; // reset the colors of the theme.
+            } else {
+                writeln!(w, "{}", name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the assets, loading them on the first call. The theme is validated if specified.
+    #[cfg(feature = "highlight")]
+    fn assets(&self, theme_name: Option<&str>) -> Result<&HighlightAssets, errors::Error> {
+        let assets = match self.assets.get() {
+            Some(assets) => assets,
+            None => {
+                let assets = App::load_assets()?;
+                self.assets.get_or_init(|| assets)
+            }
+        };
+        if let Some(name) = theme_name {
+            if !assets.has_theme(name) {
+                return Err(errors::Error::ThemeName(name.to_string()));
+            }
+        }
+        Ok(assets)
+    }
+
+    #[cfg(feature = "highlight")]
+    fn load_assets() -> Result<HighlightAssets, errors::Error> {
+        let assets = App::load_integrated_assets();
+        let assets = match UserAssetDirs::from_env() {
+            Some(dirs) => dirs.load(assets),
+            None => Ok(assets),
+        }?;
+
         debug!(
             "syntaxes: {:?}",
             assets
@@ -149,23 +543,13 @@ impl App {
             assets
                 .themes()
                 .iter()
-                .map(|&t| t
-                    .name
-                    .as_ref()
-                    .map(|s| s.as_str())
-                    .unwrap_or("** unnamed theme **"))
+                .map(|&t| t.name.as_deref().unwrap_or("** unnamed theme **"))
                 .collect::<Vec<_>>()
         );
-        Ok(App { config, assets })
-    }
-
-    pub fn run(&self) -> Result<(), errors::Error> {
-        let input_text = self.read_from_input()?;
-        let output_text = input_text.convert_to(self.config.output_format)?;
-
-        self.write_to_output(&output_text)
+        Ok(assets)
     }
 
+    #[cfg(feature = "highlight")]
     fn load_integrated_assets() -> HighlightAssets {
         HighlightAssets::new(
             from_binary(include_bytes!("../../../assets/syntaxes.bin")),
@@ -173,51 +557,200 @@ impl App {
         )
     }
 
-    fn read_from_input(&self) -> Result<FormattedText, errors::Error> {
-        // open reader
+    /// Returns true if the output is written as is, without re-indenting, highlighting and
+    /// paging, so that the input can be streamed into the output.
+    fn streams(&self, config: &Config) -> bool {
+        config.indent.is_none()
+            && self.is_plain(config)
+            && (config.output_file.is_some() || !config.paging.may_page())
+    }
+
+    /// Converts the input into the output without buffering the whole text, if the formats
+    /// support it.
+    fn stream(&self, config: &Config, converter: &Converter) -> Result<(), errors::Error> {
         let stdin = stdin();
-        let lock = stdin.lock();
-        let mut reader = if let Some(f) = self.config.input_file.as_ref() {
+        let reader = self.open_input(config, &stdin)?;
+        if let Some(f) = config.output_file.as_ref() {
+            return convert_into_file(converter, reader, Path::new(f));
+        }
+        converter.convert(reader, stdout().lock())
+    }
+
+    fn open_input<'a>(
+        &self,
+        config: &Config,
+        stdin: &'a Stdin,
+    ) -> Result<Box<dyn BufRead + 'a>, errors::Error> {
+        Ok(if let Some(f) = config.input_file.as_ref() {
             Box::new(BufReader::new(File::open(f)?)) as Box<dyn BufRead>
         } else {
-            Box::new(lock) as Box<dyn BufRead>
-        };
+            Box::new(stdin.lock()) as Box<dyn BufRead>
+        })
+    }
+
+    fn read_from_input(&self, config: &Config) -> Result<FormattedText, errors::Error> {
+        // open reader
+        let stdin = stdin();
+        let mut reader = self.open_input(config, &stdin)?;
 
         // read
-        let mut text = String::new();
-        reader.read_to_string(&mut text)?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
 
-        Ok(FormattedText::new(self.config.input_format, text))
+        Ok(FormattedText::from_bytes(config.input_format, bytes))
     }
 
-    fn write_to_output(&self, text: &FormattedText) -> Result<(), errors::Error> {
-        // open writer
-        let stdout = stdout();
-        let lock = stdout.lock();
-        let mut w = if let Some(f) = self.config.output_file.as_ref() {
-            Box::new(BufWriter::new(File::create(f)?)) as Box<dyn Write>
-        } else {
-            Box::new(lock) as Box<dyn Write>
+    #[cfg(feature = "highlight")]
+    fn select_printer<'a>(
+        &'a self,
+        config: &'a Config,
+    ) -> Result<Box<dyn Printer + 'a>, errors::Error> {
+        let highlight = &config.highlight;
+        let theme_name = highlight.theme_name.as_deref();
+        let printer = match highlight.export {
+            Some(Export::Html(style)) => Box::new(HtmlPrinter::new(
+                self.assets(theme_name)?,
+                theme_name,
+                style,
+            )) as Box<dyn Printer>,
+            Some(Export::Svg) => {
+                Box::new(SvgPrinter::new(self.assets(theme_name)?, theme_name)) as Box<dyn Printer>
+            }
+            None if config.output_file.is_none() && highlight.color_enabled => {
+                Box::new(HighlightTextPrinter::new(
+                    self.assets(theme_name)?,
+                    theme_name,
+                    highlight.color_depth,
+                    highlight.decorations.clone(),
+                )) as Box<dyn Printer>
+            }
+            None => Box::new(PlainTextPrinter::default()) as Box<dyn Printer>,
         };
+        Ok(printer)
+    }
 
-        // select printer
-        let printer = if self.config.output_file.is_none() && self.config.color_enabled {
-            Box::new(HighlightTextPrinter::new(&self.assets)) as Box<dyn Printer>
-        } else {
-            Box::new(PlainTextPrinter::default()) as Box<dyn Printer>
-        };
+    #[cfg(feature = "highlight")]
+    fn is_plain(&self, config: &Config) -> bool {
+        let highlight = &config.highlight;
+        highlight.export.is_none() && (config.output_file.is_some() || !highlight.color_enabled)
+    }
+
+    #[cfg(not(feature = "highlight"))]
+    fn is_plain(&self, _config: &Config) -> bool {
+        true
+    }
+
+    #[cfg(not(feature = "highlight"))]
+    fn select_printer(&self, _config: &Config) -> Result<Box<dyn Printer>, errors::Error> {
+        Ok(Box::new(PlainTextPrinter::default()))
+    }
+
+    fn write_to_output(&self, config: &Config, text: &FormattedText) -> Result<(), errors::Error> {
+        let printer = self.select_printer(config)?;
+
+        // print into the file
+        if let Some(f) = config.output_file.as_ref() {
+            let mut w = BufWriter::new(File::create(f)?);
+            return printer.print(&mut w, text);
+        }
+
+        // print into the pager, or STDOUT if the pager is not used
+        let mut output = Vec::new();
+        printer.print(&mut output, text)?;
+        if config.paging.should_page(&output) {
+            if let Some(pager) = Pager::from_env() {
+                if pager.page(&output)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(stdout().lock().write_all(&output)?)
+    }
+}
+
+/// Converts into a temporary file next to the output, and replaces the output with it on
+/// success. So the output is kept if the conversion fails, and can be the same file as the
+/// input, which is still read while converting.
+fn convert_into_file<R: Read>(
+    converter: &Converter,
+    reader: R,
+    path: &Path,
+) -> Result<(), errors::Error> {
+    // a symbolic link is kept, and its target is replaced.
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the output is not a file"))?;
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name.to_string_lossy(), process::id()));
 
-        // print
-        printer.print(&mut w, text)
+    let r = File::create(&temp)
+        .map_err(errors::Error::from)
+        .and_then(|file| {
+            if let Ok(metadata) = fs::metadata(&path) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            converter.convert(reader, &file)?;
+            Ok(fs::rename(&temp, &path)?)
+        });
+    if r.is_err() {
+        let _ = fs::remove_file(&temp);
     }
+    r
 }
 
 #[cfg(test)]
 mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+
+    #[cfg(feature = "highlight")]
     use syntect::dumps::from_reader;
+    #[cfg(feature = "highlight")]
     use syntect::highlighting::ThemeSet;
+    #[cfg(feature = "highlight")]
     use syntect::parsing::SyntaxSet;
 
+    use refmt::converter::Converter;
+    use refmt::format::FileFormat;
+
+    use super::convert_into_file;
+    #[cfg(feature = "highlight")]
+    use super::App;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn convert_in_place() {
+        let path = temp_file("refmt-convert-in-place.json", r#"{"a": [1, 2]}"#);
+        let converter = Converter::new().from(FileFormat::Json).to(FileFormat::Yaml);
+        let r = convert_into_file(&converter, File::open(&path).unwrap(), &path);
+        assert!(r.is_ok());
+        assert_eq!("a:\n- 1\n- 2\n", fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn keep_output_on_errors() {
+        let input = temp_file("refmt-keep-output-input.json", "{");
+        let output = temp_file("refmt-keep-output-output.yaml", "a: 1\n");
+        let converter = Converter::new().from(FileFormat::Json).to(FileFormat::Yaml);
+        let r = convert_into_file(&converter, File::open(&input).unwrap(), &output);
+        assert!(r.is_err());
+        assert_eq!("a: 1\n", fs::read_to_string(&output).unwrap());
+        // the temporary file is removed.
+        let dir = output.parent().unwrap();
+        assert!(fs::read_dir(dir).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(".refmt-keep-output-output.yaml.")));
+    }
+
+    #[cfg(feature = "highlight")]
     #[test]
     fn syntax_set_asset() -> anyhow::Result<()> {
         let bytes: &[u8] = include_bytes!("../../../assets/syntaxes.bin");
@@ -225,10 +758,18 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "highlight")]
     #[test]
     fn theme_set_asset() -> anyhow::Result<()> {
         let bytes: &[u8] = include_bytes!("../../../assets/themes.bin");
         let _themes: ThemeSet = from_reader(bytes)?;
         Ok(())
     }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn integrated_assets() -> anyhow::Result<()> {
+        App::load_integrated_assets().verify()?;
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use std::process;
 use refmt::errors;
 
 mod app;
-mod printer;
+mod pager;
 
 fn handle_error(error: &errors::Error) {
     use ansi_term::Color::Red;
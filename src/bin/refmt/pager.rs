@@ -0,0 +1,107 @@
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use log::debug;
+
+/// When to page the output to a terminal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Paging {
+    /// Only if the output doesn't fit in the terminal.
+    Auto,
+    Always,
+    Never,
+}
+
+impl Paging {
+    pub fn names() -> &'static [&'static str] {
+        &["auto", "always", "never"]
+    }
+
+    pub fn from_name(name: &str) -> Option<Paging> {
+        match name {
+            "auto" => Some(Paging::Auto),
+            "always" => Some(Paging::Always),
+            "never" => Some(Paging::Never),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the output may go through the pager, before the output is known.
+    pub fn may_page(&self) -> bool {
+        match *self {
+            Paging::Always => true,
+            Paging::Never => false,
+            Paging::Auto => atty::is(atty::Stream::Stdout),
+        }
+    }
+
+    /// Returns true if the output should go through the pager.
+    pub fn should_page(&self, output: &[u8]) -> bool {
+        match *self {
+            Paging::Always => true,
+            Paging::Never => false,
+            Paging::Auto => {
+                let lines = output.iter().filter(|&&b| b == b'\n').count();
+                atty::is(atty::Stream::Stdout)
+                    && term_size::dimensions_stdout()
+                        .is_some_and(|(_, height)| height > 0 && lines >= height)
+            }
+        }
+    }
+}
+
+pub struct Pager {
+    program: String,
+    args: Vec<String>,
+}
+
+impl Pager {
+    /// Returns `$PAGER`, or `less -R` if it is not set. `-R` is also added to `less` without
+    /// options, so that colors are shown.
+    pub fn from_env() -> Option<Pager> {
+        let command = env::var("PAGER").ok().filter(|s| !s.trim().is_empty());
+        let mut words = match command {
+            Some(command) => shell_words::split(&command).ok()?,
+            None => vec!["less".to_string()],
+        };
+        if words.is_empty() {
+            return None;
+        }
+
+        let program = words.remove(0);
+        if words.is_empty() && program == "less" {
+            words.push("-R".to_string());
+        }
+        Some(Pager {
+            program,
+            args: words,
+        })
+    }
+
+    /// Writes the output through the pager. Returns false if the pager can't be started.
+    pub fn page(&self, output: &[u8]) -> Result<bool, io::Error> {
+        debug!("pager: {} {:?}", self.program, self.args);
+        let mut child = match Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                debug!("cannot start the pager. error:{}", e);
+                return Ok(false);
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(output) {
+                // the pager was closed before reading all of the output.
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+                r => r?,
+            }
+        }
+        child.wait()?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,465 @@
+//! A builder to convert documents, for embedding refmt in other programs.
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub use refmt_serde::flatten::{Flattening, IndexStyle, KeyStyle};
+pub use refmt_serde::interpolate::Interpolation;
+use refmt_serde::Format;
+
+use crate::errors;
+use crate::format::{infer_format, FileFormat, FormattedText, Transforms};
+use crate::printer::{PlainTextPrinter, Printer};
+
+/// Converts a document from a format into another.
+///
+/// ```
+/// use refmt::converter::Converter;
+/// use refmt::format::FileFormat::{Json, Yaml};
+///
+/// let mut yaml = Vec::new();
+/// Converter::new()
+///     .from(Json)
+///     .to(Yaml)
+///     .indent(4)
+///     .convert(r#"{"a": {"b": [1, 2]}}"#.as_bytes(), &mut yaml)?;
+/// assert_eq!("a:\n    b:\n    -   1\n    -   2\n", String::from_utf8(yaml).unwrap());
+/// # Ok::<(), refmt::errors::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Converter {
+    from: Option<FileFormat>,
+    to: Option<FileFormat>,
+    indent: Option<usize>,
+    transforms: Transforms,
+}
+
+impl Converter {
+    pub fn new() -> Converter {
+        Converter::default()
+    }
+
+    /// Sets the format of the input.
+    pub fn from(mut self, format: FileFormat) -> Converter {
+        self.from = Some(format);
+        self
+    }
+
+    /// Sets the format of the output. The output has the same format as the input if omitted.
+    pub fn to(mut self, format: FileFormat) -> Converter {
+        self.to = Some(format);
+        self
+    }
+
+    /// Sets the format of the input by the extension of the file.
+    pub fn from_path<P: AsRef<Path>>(self, path: P) -> Result<Converter, errors::Error> {
+        Ok(self.from(infer_format(path.as_ref().to_str(), None)?))
+    }
+
+    /// Sets the format of the output by the extension of the file.
+    pub fn to_path<P: AsRef<Path>>(self, path: P) -> Result<Converter, errors::Error> {
+        Ok(self.to(infer_format(path.as_ref().to_str(), None)?))
+    }
+
+    /// Sets the number of spaces for each level of indentation.
+    ///
+    /// JSON, JSON5, JSONC, YAML, KDL and RON are re-indented. Other formats are written with
+    /// their own indentation. YAML needs 2 spaces at least, and uses 2 if less.
+    pub fn indent(mut self, indent: usize) -> Converter {
+        self.indent = Some(indent);
+        self
+    }
+
+    /// Expands `${VAR}` and `${VAR:-default}` in the string values after reading, and before
+    /// writing. See [`Interpolation`] for the expressions, and `${file:path}` which must be
+    /// enabled.
+    ///
+    /// ```
+    /// use refmt::converter::{Converter, Interpolation};
+    /// use refmt::format::FileFormat::Yaml;
+    ///
+    /// let mut yaml = Vec::new();
+    /// Converter::new()
+    ///     .from(Yaml)
+    ///     .interpolate(Interpolation::new().strict(true))
+    ///     .convert("url: ${DATABASE_URL:-postgres://localhost}".as_bytes(), &mut yaml)?;
+    /// assert_eq!("url: postgres://localhost\n", String::from_utf8(yaml).unwrap());
+    /// # Ok::<(), refmt::errors::Error>(())
+    /// ```
+    pub fn interpolate(mut self, interpolation: Interpolation) -> Converter {
+        self.transforms.interpolation = Some(interpolation);
+        self
+    }
+
+    /// Flattens nested objects and arrays into an object of keys like `a.b.0`, for key-value
+    /// stores.
+    ///
+    /// ```
+    /// use refmt::converter::{Converter, IndexStyle, KeyStyle};
+    /// use refmt::format::FileFormat::{Json, Yaml};
+    ///
+    /// let style = KeyStyle::new("/", IndexStyle::Bracket);
+    /// let mut yaml = Vec::new();
+    /// Converter::new()
+    ///     .from(Json)
+    ///     .to(Yaml)
+    ///     .flatten(style)
+    ///     .convert(r#"{"a": {"b": [1, 2]}}"#.as_bytes(), &mut yaml)?;
+    /// assert_eq!("a/b[0]: 1\na/b[1]: 2\n", String::from_utf8(yaml).unwrap());
+    /// # Ok::<(), refmt::errors::Error>(())
+    /// ```
+    pub fn flatten(mut self, style: KeyStyle) -> Converter {
+        self.transforms.flattening = Some(Flattening::Flatten(style));
+        self
+    }
+
+    /// Unflattens the keys of an object into nested objects and arrays, the reverse of
+    /// [`Converter::flatten`].
+    pub fn unflatten(mut self, style: KeyStyle) -> Converter {
+        self.transforms.flattening = Some(Flattening::Unflatten(style));
+        self
+    }
+
+    /// Reads a document from the reader, and writes the converted document into the writer.
+    ///
+    /// JSON into JSON is streamed without holding the document in memory if no indent
+    /// is set. See [`refmt_serde::Refmt::refmt_stream`] for the other formats.
+    pub fn convert<R: Read, W: Write>(&self, reader: R, writer: W) -> Result<(), errors::Error> {
+        let from = self.source()?;
+        let to = self.to.unwrap_or(from);
+        let formats = (Format::try_from(from), Format::try_from(to));
+        let (src_format, dest_format) = match formats {
+            (Ok(src_format), Ok(dest_format)) if self.indent.is_none() => (src_format, dest_format),
+            // plugins are converted through JSON values.
+            _ => return self.convert_with(reader, writer, &PlainTextPrinter::default()),
+        };
+
+        if from.is_write_only() {
+            return Err(errors::Error::WriteOnlyFormat(from.name()));
+        }
+        let refmt = self.transforms.refmt(src_format, dest_format);
+        Ok(refmt.refmt_stream(reader, writer)?)
+    }
+
+    /// Converts like [`Converter::convert`], and writes the output with the printer, e.g. a
+    /// [`HighlightTextPrinter`](crate::printer::HighlightTextPrinter).
+    pub fn convert_with<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        printer: &dyn Printer,
+    ) -> Result<(), errors::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let text = self.convert_text(&FormattedText::from_bytes(self.source()?, bytes))?;
+        printer.print(&mut writer, &text)
+    }
+
+    /// Converts the text. The format of the text is used if the input format is omitted.
+    pub fn convert_text(&self, text: &FormattedText) -> Result<FormattedText, errors::Error> {
+        let from = self.from.unwrap_or(text.format);
+        if from.is_write_only() {
+            return Err(errors::Error::WriteOnlyFormat(from.name()));
+        }
+
+        let to = self.to.unwrap_or(from);
+        let converted = FormattedText::from_bytes(from, text.bytes.clone())
+            .convert_to_with(to, &self.transforms)?;
+        Ok(match self.indent {
+            Some(indent) => match reindent(to, &converted.text(), indent) {
+                Some(s) => FormattedText::new(to, s),
+                None => converted,
+            },
+            None => converted,
+        })
+    }
+
+    fn source(&self) -> Result<FileFormat, errors::Error> {
+        self.from.ok_or(errors::Error::InferFormat)
+    }
+}
+
+/// Re-indents the output of the format, or returns None if the format is not supported.
+fn reindent(format: FileFormat, s: &str, indent: usize) -> Option<String> {
+    match format {
+        FileFormat::Json | FileFormat::Json5 | FileFormat::Jsonc => {
+            Some(scale(s, 2, indent, "\"'"))
+        }
+        FileFormat::Kdl => Some(scale(s, 4, indent, "\"")),
+        FileFormat::Ron => Some(scale(s, 4, indent, "\"'")),
+        FileFormat::Yaml => Some(reindent_yaml(s, indent.max(2))),
+        _ => None,
+    }
+}
+
+/// Scales the leading spaces of each line, which are multiples of `unit`. Only for the formats
+/// which escape newlines in strings.
+///
+/// The lines in a block comment, or in a string of `quotes` continued by `\`, are kept as they
+/// are, e.g. the comments of JSONC.
+fn scale(s: &str, unit: usize, indent: usize, quotes: &str) -> String {
+    let mut out = String::new();
+    let mut lexer = Lexer::default();
+    for line in s.split_inclusive('\n') {
+        if lexer.is_open() {
+            out.push_str(line);
+        } else {
+            let t = line.trim_start_matches(' ');
+            let level = (line.len() - t.len()) / unit;
+            out.push_str(&" ".repeat(level * indent));
+            out.push_str(t);
+        }
+        lexer.scan(line, quotes);
+    }
+    out
+}
+
+/// Tracks the block comments and the strings over lines.
+#[derive(Default)]
+struct Lexer {
+    comment: bool,
+    quote: Option<char>,
+}
+
+impl Lexer {
+    /// Returns true if a block comment or a string continues into the next line.
+    fn is_open(&self) -> bool {
+        self.comment || self.quote.is_some()
+    }
+
+    fn scan(&mut self, line: &str, quotes: &str) {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if self.comment {
+                if c == '*' && chars.next_if_eq(&'/').is_some() {
+                    self.comment = false;
+                }
+            } else if let Some(quote) = self.quote {
+                if c == '\\' {
+                    chars.next(); // an escaped quote, or a newline which continues the string.
+                } else if c == quote || c == '\n' {
+                    self.quote = None;
+                }
+            } else if c == '/' && chars.next_if_eq(&'/').is_some() {
+                break;
+            } else if c == '/' && chars.next_if_eq(&'*').is_some() {
+                self.comment = true;
+            } else if quotes.contains(c) {
+                self.quote = Some(c);
+            }
+        }
+    }
+}
+
+/// Re-indents a YAML written with 2 spaces, where sequence items are at the column of the key.
+///
+/// Each `- ` of sequence items is widened to the indent, so that the nested nodes stay aligned.
+/// The contents of block scalars keep their indentation relative to the parent node.
+fn reindent_yaml(s: &str, indent: usize) -> String {
+    let column = |c: usize| c / 2 * indent;
+    // (the input column, the output column) of the block scalar contents.
+    let mut block: Option<(usize, usize)> = None;
+    // (the input column, the output column, the indentation indicator) of the block scalar's parent.
+    let mut header: Option<(usize, usize, Option<usize>)> = None;
+
+    let mut out = String::new();
+    for line in s.split_inclusive('\n') {
+        let t = line.trim_start_matches(' ');
+        let leading = line.len() - t.len();
+        if t.trim().is_empty() {
+            out.push_str(t);
+            continue;
+        }
+
+        if let Some((parent, parent_out, indicator)) = header.take() {
+            if indicator.is_some() || leading > parent {
+                let content = indicator.map_or(leading, |i| parent + i);
+                let content_out = parent_out + indicator.map_or(indent, |_| indent.min(9));
+                block = Some((content, content_out));
+            }
+        }
+        if let Some((content, content_out)) = block {
+            if leading >= content {
+                out.push_str(&" ".repeat(content_out + leading - content));
+                out.push_str(t);
+                continue;
+            }
+            block = None;
+        }
+
+        let mut rest = t;
+        let mut dashes = 0usize;
+        out.push_str(&" ".repeat(column(leading)));
+        while let Some(r) = rest.strip_prefix("- ") {
+            out.push('-');
+            out.push_str(&" ".repeat(indent - 1));
+            rest = r;
+            dashes += 1;
+        }
+
+        // a block scalar, like `key: |-` or `- >`.
+        let body = rest.trim_end();
+        let value = body.rsplit_once(": ").map_or(body, |(_, v)| v);
+        if is_block_header(value) {
+            let indicator = value[1..]
+                .chars()
+                .find_map(|c| c.to_digit(10))
+                .map(|i| i as usize);
+            // the parent is the key, or the last `- ` if the scalar is an item.
+            let items = if value.len() < body.len() {
+                dashes
+            } else {
+                dashes.saturating_sub(1)
+            };
+            header = Some((
+                leading + items * 2,
+                column(leading) + items * indent,
+                indicator,
+            ));
+            if let Some(i) = indicator {
+                out.push_str(&body[..body.len() - value.len()]);
+                out.push_str(&value.replacen(&i.to_string(), &indent.min(9).to_string(), 1));
+                out.push_str(&rest[body.len()..]);
+                continue;
+            }
+        }
+        out.push_str(rest);
+    }
+    out
+}
+
+fn is_block_header(value: &str) -> bool {
+    value.starts_with(['|', '>'])
+        && value[1..]
+            .chars()
+            .all(|c| c == '+' || c == '-' || c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reindent_yaml, Converter, IndexStyle, Interpolation, KeyStyle};
+    use crate::errors;
+    use crate::format::FileFormat;
+
+    fn convert(converter: Converter, s: &str) -> String {
+        let mut out = Vec::new();
+        converter.convert(s.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn indent_json() {
+        let converter = Converter::new().from(FileFormat::Yaml).to(FileFormat::Json);
+        assert_eq!(
+            "{\n    \"a\": [\n        1\n    ]\n}\n",
+            convert(converter.indent(4), "a: [1]")
+        );
+
+        // the lines of block comments are kept.
+        let converter = Converter::new().from(FileFormat::Jsonc).indent(4);
+        let jsonc = "{\n  /* a\n     b */\n  \"a\": \"/*\", // /* c\n  \"b\": [1]\n}\n";
+        assert_eq!(
+            "{\n    /* a\n     b */\n    \"a\": \"/*\", // /* c\n    \"b\": [\n        1\n    ]\n}\n",
+            convert(converter, jsonc)
+        );
+    }
+
+    #[test]
+    fn indent_yaml() {
+        let yaml = r#"a:
+  b:
+  - 1
+  - c: |-
+      x
+      y
+    d:
+    - - 1
+      - 2
+  e: |2-
+      lead
+    x
+"#;
+        assert_eq!(
+            r#"a:
+    b:
+    -   1
+    -   c: |-
+            x
+            y
+        d:
+        -   -   1
+            -   2
+    e: |4-
+          lead
+        x
+"#,
+            reindent_yaml(yaml, 4)
+        );
+        assert_eq!(yaml, reindent_yaml(yaml, 2));
+
+        // the values are kept.
+        let to_json = Converter::new().from(FileFormat::Yaml).to(FileFormat::Json);
+        let json = convert(to_json.clone(), yaml);
+        for indent in 3..=10 {
+            assert_eq!(json, convert(to_json.clone(), &reindent_yaml(yaml, indent)));
+        }
+    }
+
+    #[test]
+    fn infer_formats() -> Result<(), errors::Error> {
+        let converter = Converter::new().from_path("a.json")?.to_path("b.yml")?;
+        assert_eq!("a: 1\n", convert(converter, r#"{"a": 1}"#));
+
+        // the output format is the same as the input if omitted.
+        let converter = Converter::new().from(FileFormat::Json).indent(4);
+        assert_eq!("{\n    \"a\": 1\n}\n", convert(converter, r#"{"a":1}"#));
+
+        let mut out = Vec::new();
+        let r = Converter::new().convert("{}".as_bytes(), &mut out);
+        assert!(matches!(r, Err(errors::Error::InferFormat)));
+        Ok(())
+    }
+
+    #[test]
+    fn interpolate() {
+        std::env::set_var("REFMT_CONVERTER_REGION", "eu-west-1");
+        let converter = Converter::new()
+            .from(FileFormat::Json)
+            .to(FileFormat::Yaml)
+            .interpolate(Interpolation::default());
+        let json = r#"{"region": "${REFMT_CONVERTER_REGION}", "zone": "${REFMT_CONVERTER_ZONE}"}"#;
+        assert_eq!(
+            "region: eu-west-1\nzone: ''\n",
+            convert(converter.clone(), json)
+        );
+        // re-indented.
+        assert_eq!(
+            "{\n    \"region\": \"eu-west-1\",\n    \"zone\": \"\"\n}\n",
+            convert(converter.clone().to(FileFormat::Json).indent(4), json)
+        );
+
+        let mut out = Vec::new();
+        let r = converter
+            .interpolate(Interpolation::new().strict(true))
+            .convert(json.as_bytes(), &mut out);
+        assert!(matches!(r, Err(errors::Error::RefmtError(_))));
+    }
+
+    #[test]
+    fn flatten() {
+        let style = KeyStyle::new("_", IndexStyle::Dot);
+        let flatten = Converter::new()
+            .from(FileFormat::Yaml)
+            .to(FileFormat::Toml)
+            .flatten(style.clone());
+        let yaml = "db:\n  hosts:\n  - a\n  - b\n";
+        let toml = convert(flatten, yaml);
+        assert_eq!("db_hosts_0 = \"a\"\ndb_hosts_1 = \"b\"\n", toml);
+
+        let unflatten = Converter::new()
+            .from(FileFormat::Toml)
+            .to(FileFormat::Yaml)
+            .unflatten(style);
+        assert_eq!(yaml, convert(unflatten, &toml));
+    }
+}
@@ -4,6 +4,7 @@ use refmt_serde::RefmtError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("IO Error. cause:{_0}")]
     Io(#[from] io::Error),
@@ -14,9 +15,30 @@ pub enum Error {
     #[error("Unsupported format name. name:{_0}")]
     FormatName(String),
 
+    #[error("Cannot read {_0}, which is an output only format")]
+    WriteOnlyFormat(&'static str),
+
+    #[error("Unknown theme name. name:{_0}, see `refmt --list-themes` for available themes")]
+    ThemeName(String),
+
+    #[error("No syntax for highlighting. extension:{_0}")]
+    SyntaxName(String),
+
     #[error("Cannot infer format. Please specify either FILE or FORMAT")]
     InferFormat,
 
-    #[error("Cannot create assets")]
+    #[error("Cannot create assets. cause:{_0}")]
     CreatingAssets(String),
+
+    #[error("Invalid assets. cause:{_0}")]
+    InvalidAssets(String),
+
+    #[error("Format is already registered. name:{_0}")]
+    DuplicateFormat(String),
+
+    #[error("Any errors occurred on the format plugin. name:{name}, cause:{cause}")]
+    Plugin {
+        name: &'static str,
+        cause: Box<dyn std::error::Error + Send + Sync>,
+    },
 }
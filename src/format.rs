@@ -1,37 +1,64 @@
+use std::borrow::Cow;
+use std::path::Path;
 use std::str::FromStr;
 
+use refmt_serde::flatten::Flattening;
+use refmt_serde::interpolate::Interpolation;
 use refmt_serde::{Format, Refmt};
-use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::errors;
+use crate::registry::{self, Codec};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, EnumIter)]
+#[non_exhaustive]
 pub enum FileFormat {
+    Bson,
+    Edn,
+    Hcl,
     Json,
+    Json5,
+    Jsonc,
+    Kdl,
+    Plist,
+    BinaryPlist,
+    Ron,
     Toml,
     Yaml,
+    MarkdownTable,
+    HtmlTable,
+    /// A format registered by [`registry::register`], which is identified by its name.
+    #[strum(disabled)]
+    Plugin(&'static str),
 }
 
 impl FileFormat {
+    /// Returns the bundled formats and the registered plugins.
+    pub fn all() -> Vec<FileFormat> {
+        registry::entries().iter().map(|e| e.format()).collect()
+    }
+
     pub fn names() -> Vec<&'static str> {
-        FileFormat::iter().map(|f| f.name()).collect()
+        registry::entries().iter().map(|e| e.name()).collect()
+    }
+
+    pub fn readable_names() -> Vec<&'static str> {
+        registry::entries()
+            .iter()
+            .filter(|e| !e.is_write_only())
+            .map(|e| e.name())
+            .collect()
     }
 
     pub fn name(&self) -> &'static str {
         match *self {
-            FileFormat::Json => "json",
-            FileFormat::Toml => "toml",
-            FileFormat::Yaml => "yaml",
+            FileFormat::Plugin(name) => name,
+            _ => self.entry().map_or("", |e| e.name()),
         }
     }
 
     pub fn extensions(&self) -> &[&'static str] {
-        match *self {
-            FileFormat::Json => &["json"],
-            FileFormat::Toml => &["toml"],
-            FileFormat::Yaml => &["yaml", "yml"],
-        }
+        self.entry().map_or(&[], |e| e.extensions())
     }
 
     pub fn is_extension(&self, s: &str) -> bool {
@@ -41,6 +68,23 @@ impl FileFormat {
     pub fn preferred_extension(&self) -> &'static str {
         self.name()
     }
+
+    pub fn syntax_extension(&self) -> &'static str {
+        self.entry().map_or("txt", |e| e.syntax_extension())
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.entry().is_some_and(|e| e.is_binary())
+    }
+
+    /// Returns true if the format can be an output, but not an input.
+    pub fn is_write_only(&self) -> bool {
+        self.entry().is_some_and(|e| e.is_write_only())
+    }
+
+    fn entry(&self) -> Option<registry::Entry> {
+        registry::get(*self)
+    }
 }
 
 impl FromStr for FileFormat {
@@ -48,40 +92,108 @@ impl FromStr for FileFormat {
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
         let lower = s.to_ascii_lowercase();
-        Ok(FileFormat::iter()
-            .find(|f| f.is_extension(&lower))
-            .ok_or(errors::Error::FormatName(s.to_string()))?)
+        registry::find(&lower)
+            .map(|e| e.format())
+            .ok_or(errors::Error::FormatName(s.to_string()))
+    }
+}
+
+impl TryFrom<FileFormat> for Format {
+    type Error = errors::Error;
+
+    /// Plugins have no formats of refmt-serde, and are converted through JSON values.
+    fn try_from(value: FileFormat) -> Result<Self, Self::Error> {
+        match value.entry().map(|e| e.codec()) {
+            Some(Codec::Bundled(format)) => Ok(format),
+            _ => Err(errors::Error::FormatName(value.name().to_string())),
+        }
+    }
+}
+
+/// Returns the format name, or the extension of the file if the name is omitted.
+pub fn infer_format_name<'a>(
+    file: Option<&'a str>,
+    format_name: Option<&'a str>,
+) -> Option<&'a str> {
+    if let Some(format_name) = format_name {
+        Some(format_name)
+    } else if let Some(file) = file {
+        Path::new(file).extension().and_then(|ext| ext.to_str())
+    } else {
+        None
+    }
+}
+
+/// Infers the format by the name, or the extension of the file if the name is omitted.
+pub fn infer_format(
+    file: Option<&str>,
+    format_name: Option<&str>,
+) -> Result<FileFormat, errors::Error> {
+    let format_name = infer_format_name(file, format_name);
+    if let Some(format_name) = format_name {
+        FileFormat::from_str(format_name)
+    } else {
+        Err(errors::Error::InferFormat)
     }
 }
 
-impl From<FileFormat> for Format {
-    fn from(value: FileFormat) -> Self {
-        match value {
-            FileFormat::Json => Format::Json,
-            FileFormat::Yaml => Format::Yaml,
-            FileFormat::Toml => Format::Toml,
+/// The transforms of the value, which are applied after reading and before writing.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Transforms {
+    pub interpolation: Option<Interpolation>,
+    pub flattening: Option<Flattening>,
+}
+
+impl Transforms {
+    /// Returns the converter of the formats with the transforms.
+    pub fn refmt(&self, src_format: Format, dest_format: Format) -> Refmt {
+        let mut refmt = Refmt::new(src_format, dest_format);
+        if let Some(interpolation) = self.interpolation {
+            refmt = refmt.interpolate(interpolation);
+        }
+        if let Some(flattening) = &self.flattening {
+            refmt = refmt.flatten(flattening.clone());
         }
+        refmt
     }
 }
 
 pub struct FormattedText {
     pub format: FileFormat,
-    pub text: String,
+    pub bytes: Vec<u8>,
 }
 
 impl FormattedText {
     pub fn new(format: FileFormat, text: String) -> FormattedText {
-        FormattedText { format, text }
+        FormattedText::from_bytes(format, text.into_bytes())
+    }
+
+    pub fn from_bytes(format: FileFormat, bytes: Vec<u8>) -> FormattedText {
+        FormattedText { format, bytes }
+    }
+
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.bytes)
     }
 
     pub fn convert_to(&self, format: FileFormat) -> Result<FormattedText, errors::Error> {
-        let refmt = Refmt {
-            src_format: Format::from(self.format),
-            dest_format: Format::from(format),
-        };
+        self.convert_to_with(format, &Transforms::default())
+    }
+
+    /// Converts like [`FormattedText::convert_to`], and transforms the value between reading
+    /// and writing.
+    pub(crate) fn convert_to_with(
+        &self,
+        format: FileFormat,
+        transforms: &Transforms,
+    ) -> Result<FormattedText, errors::Error> {
+        if matches!(self.format, FileFormat::Plugin(_)) || matches!(format, FileFormat::Plugin(_)) {
+            return registry::convert(self, format, transforms);
+        }
 
-        let text = refmt.refmt(&self.text)?;
-        Ok(FormattedText { text, format })
+        let refmt = transforms.refmt(Format::try_from(self.format)?, Format::try_from(format)?);
+        let bytes = refmt.refmt_bytes(&self.bytes)?;
+        Ok(FormattedText { bytes, format })
     }
 }
 
@@ -89,7 +201,7 @@ impl FormattedText {
 mod tests {
     use super::*;
 
-    static JSON_TEXT: &'static str = r#"{
+    static JSON_TEXT: &str = r#"{
   "id": 123,
   "title": "Lorem ipsum dolor sit amet",
   "author": {
@@ -100,7 +212,7 @@ mod tests {
 }
 "#;
 
-    static YAML_TEXT: &'static str = r#"id: 123
+    static YAML_TEXT: &str = r#"id: 123
 title: Lorem ipsum dolor sit amet
 author:
   id: 999
@@ -113,12 +225,40 @@ author:
         assert_eq!(FileFormat::Json, FileFormat::from_str("json").unwrap());
         assert_eq!(FileFormat::Json, FileFormat::from_str("JsOn").unwrap());
 
+        assert_eq!(FileFormat::Json5, FileFormat::from_str("json5").unwrap());
+        assert_eq!(FileFormat::Jsonc, FileFormat::from_str("jsonc").unwrap());
+
+        assert_eq!(FileFormat::Ron, FileFormat::from_str("ron").unwrap());
         assert_eq!(FileFormat::Toml, FileFormat::from_str("toml").unwrap());
         assert_eq!(FileFormat::Yaml, FileFormat::from_str("yaml").unwrap());
         assert_eq!(FileFormat::Yaml, FileFormat::from_str("yml").unwrap());
+        assert_eq!(FileFormat::Bson, FileFormat::from_str("bson").unwrap());
+        assert_eq!(FileFormat::Hcl, FileFormat::from_str("hcl").unwrap());
+        assert_eq!(FileFormat::Hcl, FileFormat::from_str("tf").unwrap());
+        assert_eq!(FileFormat::Hcl, FileFormat::from_str("tfvars").unwrap());
+        assert_eq!(FileFormat::Plist, FileFormat::from_str("plist").unwrap());
+        assert_eq!(
+            FileFormat::BinaryPlist,
+            FileFormat::from_str("bplist").unwrap()
+        );
+        assert_eq!(FileFormat::Kdl, FileFormat::from_str("kdl").unwrap());
+        assert_eq!(FileFormat::Edn, FileFormat::from_str("edn").unwrap());
+        assert_eq!(
+            FileFormat::MarkdownTable,
+            FileFormat::from_str("markdown-table").unwrap()
+        );
+        assert_eq!(
+            FileFormat::HtmlTable,
+            FileFormat::from_str("html-table").unwrap()
+        );
+
+        assert!(FileFormat::names().contains(&"markdown-table"));
+        assert!(!FileFormat::readable_names().contains(&"markdown-table"));
 
         let r = FileFormat::from_str("conf"); // HOCON
         assert!(r.is_err());
+        assert!(FileFormat::from_str("md").is_err());
+        assert!(FileFormat::from_str("html").is_err());
     }
 
     #[test]
@@ -137,13 +277,13 @@ id = 999
 first_name = "John"
 last_name = "Doe"
 "#,
-            r.as_ref().ok().unwrap().text
+            r.as_ref().ok().unwrap().text()
         );
 
         // JSON => YAML
         let r = text.convert_to(FileFormat::Yaml);
         assert!(r.is_ok());
-        assert_eq!(YAML_TEXT, r.as_ref().ok().unwrap().text);
+        assert_eq!(YAML_TEXT, r.as_ref().ok().unwrap().text());
 
         // Error
         let text = FormattedText::new(FileFormat::Json, YAML_TEXT.to_string());
@@ -169,12 +309,12 @@ last_name = "Doe"
         // TOML => JSON
         let r = text.convert_to(FileFormat::Json);
         assert!(r.is_ok());
-        assert_eq!(JSON_TEXT, r.as_ref().ok().unwrap().text);
+        assert_eq!(JSON_TEXT, r.as_ref().ok().unwrap().text());
 
         // TOML => YAML
         let r = text.convert_to(FileFormat::Yaml);
         assert!(r.is_ok());
-        assert_eq!(YAML_TEXT, r.as_ref().ok().unwrap().text);
+        assert_eq!(YAML_TEXT, r.as_ref().ok().unwrap().text());
 
         // Error
         let text = FormattedText::new(FileFormat::Toml, JSON_TEXT.to_string());
@@ -189,7 +329,7 @@ last_name = "Doe"
         // YAML => JSON
         let r = text.convert_to(FileFormat::Json);
         assert!(r.is_ok());
-        assert_eq!(JSON_TEXT, r.as_ref().ok().unwrap().text);
+        assert_eq!(JSON_TEXT, r.as_ref().ok().unwrap().text());
 
         // YAML => TOML
         let r = text.convert_to(FileFormat::Toml);
@@ -203,9 +343,16 @@ id = 999
 first_name = "John"
 last_name = "Doe"
 "#,
-            r.as_ref().ok().unwrap().text
+            r.as_ref().ok().unwrap().text()
         );
 
+        // YAML => BSON => YAML
+        let r = text.convert_to(FileFormat::Bson);
+        assert!(r.is_ok());
+        let r = r.ok().unwrap().convert_to(FileFormat::Yaml);
+        assert!(r.is_ok());
+        assert_eq!(YAML_TEXT, r.as_ref().ok().unwrap().text());
+
         // Error
         // TODO: this test will be panicked on `r.is_err()`. need to survey why the panic occurs..
         //        let text = FormattedText::new(Format::Yaml, TOML_TEXT.to_string());
@@ -1,3 +1,14 @@
+//! refmt converts documents between data formats, such as JSON, YAML and TOML.
+//!
+//! [`converter::Converter`] is the entry point for embedding refmt, and [`printer`] has the
+//! printers which the command line tool uses to write the output. Other formats can be added
+//! with [`registry`].
+
+#[cfg(feature = "highlight")]
 pub mod assets;
+pub mod converter;
 pub mod errors;
 pub mod format;
+pub mod pointer;
+pub mod printer;
+pub mod registry;
@@ -0,0 +1,328 @@
+//! JSON pointers of the values on each line of a converted text.
+
+use crate::format::FileFormat;
+
+/// Returns the JSON pointer of the value on each line, or None if the format is not supported.
+///
+/// Only JSON-like and YAML texts are supported, since they are written with a line for each
+/// value. Closing brackets have the pointer of their container, and blank lines an empty one.
+pub fn line_pointers(format: FileFormat, text: &str) -> Option<Vec<String>> {
+    match format {
+        FileFormat::Json | FileFormat::Json5 | FileFormat::Jsonc => Some(json_pointers(text)),
+        FileFormat::Yaml => Some(yaml_pointers(text)),
+        _ => None,
+    }
+}
+
+fn push(path: &str, key: &str) -> String {
+    format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Splits a quoted key from the head of `s`, and returns the unquoted key and the rest.
+fn quoted_key(s: &str) -> Option<(String, &str)> {
+    let quote = s.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let mut escaped = false;
+    let mut key = String::new();
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => {
+                escaped = false;
+                key.push(match c {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    c => c,
+                });
+            }
+            '\\' if quote == '"' => escaped = true,
+            '\'' if quote == '\'' && s[i + 1..].starts_with('\'') => escaped = true,
+            c if c == quote => return Some((key, &s[i + 1..])),
+            c => key.push(c),
+        }
+    }
+    None
+}
+
+enum Container {
+    Object(String),
+    Array(String, usize),
+}
+
+fn json_pointers(text: &str) -> Vec<String> {
+    let mut stack = Vec::<Container>::new();
+    let mut pointers = Vec::new();
+    for line in text.lines() {
+        let t = line.trim();
+        if t.is_empty() || t.starts_with("//") || t.starts_with("/*") {
+            pointers.push(String::new());
+            continue;
+        }
+        if t.starts_with('}') || t.starts_with(']') {
+            let pointer = match stack.pop() {
+                Some(Container::Object(path)) | Some(Container::Array(path, _)) => path,
+                None => String::new(),
+            };
+            pointers.push(pointer);
+            continue;
+        }
+
+        let (pointer, value) = match stack.last_mut() {
+            None => (String::new(), t),
+            Some(Container::Object(path)) => match quoted_key(t) {
+                Some((key, rest)) => (push(path, &key), rest.trim_start_matches(':').trim()),
+                None => match t.split_once(':') {
+                    // JSON5 allows unquoted keys.
+                    Some((key, rest)) => (push(path, key.trim()), rest.trim()),
+                    None => (path.clone(), t),
+                },
+            },
+            Some(Container::Array(path, i)) => {
+                *i += 1;
+                (push(path, &(*i - 1).to_string()), t)
+            }
+        };
+        match value.trim_end_matches(',') {
+            "{" => stack.push(Container::Object(pointer.clone())),
+            "[" => stack.push(Container::Array(pointer.clone(), 0)),
+            _ => {}
+        }
+        pointers.push(pointer);
+    }
+    pointers
+}
+
+struct Block {
+    indent: usize,
+    container: Container,
+}
+
+fn yaml_pointers(text: &str) -> Vec<String> {
+    let mut stack = Vec::<Block>::new();
+    // a key without a value, whose value is on the following lines.
+    let mut pending: Option<(usize, String)> = None;
+    // a key with a block scalar, or the last value which may continue on the following lines.
+    let mut last: Option<(usize, String)> = None;
+
+    let mut pointers = Vec::new();
+    for line in text.lines() {
+        let t = line.trim_start();
+        let indent = line.len() - t.len();
+        if t.is_empty() || t.starts_with('#') || t == "---" {
+            pointers.push(String::new());
+            continue;
+        }
+
+        if let Some((_, pointer)) = last.as_ref().filter(|(i, _)| indent > *i) {
+            if pending.is_none() {
+                pointers.push(pointer.clone());
+                continue;
+            }
+        }
+
+        if let Some((parent, pointer)) = pending.take() {
+            let is_item = t.starts_with("- ") || t == "-";
+            if indent > parent || (indent == parent && is_item) {
+                let container = if is_item {
+                    Container::Array(pointer, 0)
+                } else {
+                    Container::Object(pointer)
+                };
+                stack.push(Block { indent, container });
+            }
+        }
+        while let Some(top) = stack.last() {
+            let is_item = t.starts_with("- ") || t == "-";
+            let mismatched =
+                top.indent == indent && matches!(top.container, Container::Array(..)) != is_item;
+            if top.indent > indent || mismatched {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        let mut indent = indent;
+        let mut t = t;
+        let mut pointer = String::new();
+        loop {
+            if t.starts_with("- ") || t == "-" {
+                if stack.last().is_none_or(|b| b.indent != indent) {
+                    let path = stack
+                        .last()
+                        .map(|b| path_of(b).to_string())
+                        .unwrap_or_default();
+                    stack.push(Block {
+                        indent,
+                        container: Container::Array(path, 0),
+                    });
+                }
+                if let Some(Block {
+                    container: Container::Array(path, i),
+                    ..
+                }) = stack.last_mut()
+                {
+                    *i += 1;
+                    pointer = push(path, &(*i - 1).to_string());
+                }
+
+                let rest = t[1..].trim_start();
+                if rest.is_empty() {
+                    pending = Some((indent, pointer.clone()));
+                    break;
+                }
+                // a compact mapping or sequence in the item, like `- key: value`.
+                indent += t.len() - rest.len();
+                t = rest;
+                if is_key(t) {
+                    stack.push(Block {
+                        indent,
+                        container: Container::Object(pointer.clone()),
+                    });
+                } else if t.starts_with("- ") {
+                    stack.push(Block {
+                        indent,
+                        container: Container::Array(pointer.clone(), 0),
+                    });
+                } else {
+                    last = Some((indent - 2, pointer.clone()));
+                    break;
+                }
+                continue;
+            }
+
+            let (key, value) = match split_key(t) {
+                Some(kv) => kv,
+                None => {
+                    // a scalar document, or a continuation of the last scalar.
+                    pointer = last.as_ref().map(|(_, p)| p.clone()).unwrap_or(pointer);
+                    break;
+                }
+            };
+            if stack.last().is_none_or(|b| b.indent != indent) {
+                let path = stack
+                    .last()
+                    .map(|b| path_of(b).to_string())
+                    .unwrap_or_default();
+                stack.push(Block {
+                    indent,
+                    container: Container::Object(path),
+                });
+            }
+            pointer = push(stack.last().map(path_of).unwrap_or_default(), &key);
+            if value.is_empty() {
+                pending = Some((indent, pointer.clone()));
+            }
+            last = Some((indent, pointer.clone()));
+            break;
+        }
+        pointers.push(pointer);
+    }
+    pointers
+}
+
+fn path_of(b: &Block) -> &str {
+    match &b.container {
+        Container::Object(path) | Container::Array(path, _) => path,
+    }
+}
+
+fn is_key(s: &str) -> bool {
+    split_key(s).is_some()
+}
+
+/// Splits `key: value` into the unquoted key and the value.
+fn split_key(s: &str) -> Option<(String, &str)> {
+    if let Some((key, rest)) = quoted_key(s) {
+        return rest
+            .strip_prefix(':')
+            .filter(|r| r.is_empty() || r.starts_with(' '))
+            .map(|r| (key, r.trim()));
+    }
+    if s.starts_with(['[', '{', '|', '>', '&', '*', '!']) {
+        return None;
+    }
+    match s.find(": ") {
+        Some(i) => Some((s[..i].to_string(), s[i + 2..].trim())),
+        None => s.strip_suffix(':').map(|k| (k.to_string(), "")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line_pointers;
+    use crate::format::FileFormat;
+
+    #[test]
+    fn json_pointers() {
+        let text = r#"{
+  "id": 123,
+  "a/b": {
+    "tags": [
+      "json",
+      {
+        "x": null
+      }
+    ],
+    "empty": []
+  }
+}
+"#;
+        let pointers = line_pointers(FileFormat::Json, text).unwrap();
+        assert_eq!(
+            vec![
+                "",
+                "/id",
+                "/a~1b",
+                "/a~1b/tags",
+                "/a~1b/tags/0",
+                "/a~1b/tags/1",
+                "/a~1b/tags/1/x",
+                "/a~1b/tags/1",
+                "/a~1b/tags",
+                "/a~1b/empty",
+                "/a~1b",
+                "",
+            ],
+            pointers
+        );
+    }
+
+    #[test]
+    fn yaml_pointers() {
+        let text = r#"id: 123
+author:
+  name: John
+  tags:
+  - json
+  - id: 1
+    nested:
+    - a
+  - - x
+description: |-
+  multi
+  line
+'a: b': 1
+"#;
+        let pointers = line_pointers(FileFormat::Yaml, text).unwrap();
+        assert_eq!(
+            vec![
+                "/id",
+                "/author",
+                "/author/name",
+                "/author/tags",
+                "/author/tags/0",
+                "/author/tags/1/id",
+                "/author/tags/1/nested",
+                "/author/tags/1/nested/0",
+                "/author/tags/2/0",
+                "/description",
+                "/description",
+                "/description",
+                "/a: b",
+            ],
+            pointers
+        );
+
+        assert!(line_pointers(FileFormat::Toml, "id = 1").is_none());
+    }
+}
@@ -0,0 +1,689 @@
+use std::env;
+use std::io::Write;
+
+use ansi_term::Style as AnsiStyle;
+use log::debug;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, FontStyle, Style, Theme};
+use syntect::util::as_24_bit_terminal_escaped;
+
+use crate::assets::HighlightAssets;
+use crate::errors;
+use crate::format::FormattedText;
+use crate::pointer::line_pointers;
+
+use super::{PlainTextPrinter, Printer};
+
+/// The number of colors which the terminal can show.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    pub fn names() -> &'static [&'static str] {
+        &["truecolor", "256", "16"]
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorDepth> {
+        match name {
+            "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Ansi256),
+            "16" => Some(ColorDepth::Ansi16),
+            _ => None,
+        }
+    }
+
+    /// Detects the color depth by `COLORTERM` and `TERM`.
+    pub fn from_env() -> ColorDepth {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        ColorDepth::from_terms(&colorterm, &term)
+    }
+
+    /// True colors are used if `COLORTERM` tells so. Otherwise `TERM` of `*256color` has 256
+    /// colors, and the Linux console or `*-16color` has 16 colors. Other terminals are assumed
+    /// to have true colors.
+    fn from_terms(colorterm: &str, term: &str) -> ColorDepth {
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorDepth::TrueColor
+        } else if term.ends_with("256color") {
+            ColorDepth::Ansi256
+        } else if term == "linux" || term.ends_with("-16color") {
+            ColorDepth::Ansi16
+        } else {
+            ColorDepth::TrueColor
+        }
+    }
+}
+
+/// The 16 ANSI colors of xterm.
+const ANSI16_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn ansi16_from_rgb(c: Color) -> u8 {
+    let distance = |&(r, g, b): &(u8, u8, u8)| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, c.r) + d(g, c.g) + d(b, c.b)
+    };
+    let (i, _) = ANSI16_PALETTE
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, rgb)| distance(rgb))
+        .unwrap();
+    i as u8
+}
+
+/// Escapes the foreground colors quantized to the color depth. Backgrounds are not painted, since
+/// they rarely match the terminal's own background after quantization.
+fn as_quantized_terminal_escaped(ranges: &[(Style, &str)], depth: ColorDepth) -> String {
+    let mut s = String::new();
+    for (style, text) in ranges {
+        let c = style.foreground;
+        let code = match depth {
+            ColorDepth::TrueColor => format!("38;2;{};{};{}", c.r, c.g, c.b),
+            ColorDepth::Ansi256 => {
+                format!("38;5;{}", ansi_colours::ansi256_from_rgb((c.r, c.g, c.b)))
+            }
+            ColorDepth::Ansi16 => match ansi16_from_rgb(c) {
+                i @ 0..=7 => (30 + i).to_string(),
+                i => (90 + i - 8).to_string(),
+            },
+        };
+        s.push_str(&format!("\x1b[{}m{}", code, text));
+    }
+    s.push_str("\x1b[0m");
+    s
+}
+
+/// The highlighted ranges of a text, and the theme used.
+type Highlighted<'a, 's> = (Vec<(Style, &'s str)>, &'a Theme);
+
+/// Highlights the text with the theme, or the theme for its syntax if omitted.
+fn highlight<'a, 's>(
+    assets: &'a HighlightAssets,
+    theme_name: Option<&str>,
+    text: &FormattedText,
+    s: &'s str,
+) -> Result<Highlighted<'a, 's>, errors::Error> {
+    let syntax = assets.get_syntax_or_plain_text(text.format.syntax_extension())?;
+    let theme = match theme_name {
+        Some(name) => assets.get_theme_or_default(name),
+        None => assets.get_theme_for_syntax(syntax),
+    };
+    let mut highlight = HighlightLines::new(syntax, theme);
+    let ranges = highlight
+        .highlight_line(s, &assets.syntax_set)
+        .map_err(|e| errors::Error::InvalidAssets(e.to_string()))?;
+    Ok((ranges, theme))
+}
+
+/// Decorations around the highlighted text.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Decorations {
+    pub numbers: bool,
+    pub grid: bool,
+    /// A title of the text, like `config.toml → yaml`.
+    pub header: Option<String>,
+    /// Annotates each line with the JSON pointer of the value it contains.
+    pub pointers: bool,
+}
+
+impl Decorations {
+    pub fn names() -> &'static [&'static str] {
+        &["plain", "numbers", "grid", "header", "pointers", "full"]
+    }
+
+    /// Builds the decorations from their names. `full` is all but the pointers.
+    pub fn from_names<'a>(names: impl IntoIterator<Item = &'a str>, title: &str) -> Decorations {
+        let mut decorations = Decorations::default();
+        for name in names {
+            match name {
+                "numbers" => decorations.numbers = true,
+                "grid" => decorations.grid = true,
+                "header" => decorations.header = Some(title.to_string()),
+                "pointers" => decorations.pointers = true,
+                "full" => {
+                    decorations.numbers = true;
+                    decorations.grid = true;
+                    decorations.header = Some(title.to_string());
+                }
+                _ => {}
+            }
+        }
+        decorations
+    }
+
+    fn is_plain(&self) -> bool {
+        *self == Decorations::default()
+    }
+}
+
+pub struct HighlightTextPrinter<'a> {
+    assets: &'a HighlightAssets,
+    theme_name: Option<&'a str>,
+    color_depth: ColorDepth,
+    decorations: Decorations,
+}
+
+impl<'a> HighlightTextPrinter<'a> {
+    pub fn new(
+        assets: &'a HighlightAssets,
+        theme_name: Option<&'a str>,
+        color_depth: ColorDepth,
+        decorations: Decorations,
+    ) -> Self {
+        HighlightTextPrinter {
+            assets,
+            theme_name,
+            color_depth,
+            decorations,
+        }
+    }
+
+    fn escape(&self, ranges: &[(Style, &str)]) -> String {
+        match self.color_depth {
+            ColorDepth::TrueColor => as_24_bit_terminal_escaped(ranges, true),
+            depth => as_quantized_terminal_escaped(ranges, depth),
+        }
+    }
+}
+
+impl<'a> Printer for HighlightTextPrinter<'a> {
+    fn print(&self, dest: &mut dyn Write, text: &FormattedText) -> Result<(), errors::Error> {
+        if text.format.is_binary() {
+            return PlainTextPrinter::default().print(dest, text);
+        }
+
+        let s = text.text();
+        let (ranges, _) = highlight(self.assets, self.theme_name, text, &s)?;
+        if self.decorations.is_plain() {
+            return write!(dest, "{}", self.escape(&ranges)).map_err(errors::Error::from);
+        }
+
+        let lines = split_lines(&ranges)
+            .iter()
+            .map(|line| {
+                let width = line.iter().map(|(_, t)| t.chars().count()).sum::<usize>();
+                // reset the colors, so that they don't leak into the decorations.
+                (format!("{}\x1b[0m", self.escape(line)), width)
+            })
+            .collect::<Vec<_>>();
+        let pointers = if self.decorations.pointers {
+            let pointers = line_pointers(text.format, &s);
+            if pointers.is_none() {
+                debug!("pointers are not supported for {}", text.format.name());
+            }
+            pointers
+        } else {
+            None
+        };
+        let width = term_size::dimensions_stdout()
+            .map(|(w, _)| w)
+            .filter(|&w| w > 0)
+            .unwrap_or(80);
+        Ok(write!(
+            dest,
+            "{}",
+            decorate(&lines, pointers.as_deref(), &self.decorations, width)
+        )?)
+    }
+}
+
+/// Splits the highlighted ranges into lines, without the newlines.
+fn split_lines<'s>(ranges: &[(Style, &'s str)]) -> Vec<Vec<(Style, &'s str)>> {
+    let mut lines = vec![Vec::new()];
+    for (style, text) in ranges {
+        let mut parts = text.split('\n');
+        if let Some(part) = parts.next() {
+            lines.last_mut().unwrap().push((*style, part));
+        }
+        for part in parts {
+            lines.push(vec![(*style, part)]);
+        }
+    }
+    if lines
+        .last()
+        .is_some_and(|l| l.iter().all(|(_, t)| t.is_empty()))
+    {
+        lines.pop();
+    }
+    lines
+}
+
+/// Lays out the escaped lines and their widths with the decorations, in a terminal of `width`
+/// columns.
+fn decorate(
+    lines: &[(String, usize)],
+    pointers: Option<&[String]>,
+    decorations: &Decorations,
+    width: usize,
+) -> String {
+    let dimmed = AnsiStyle::new().dimmed();
+    let digits = lines.len().to_string().len().max(3);
+    let gutter_width = if decorations.numbers { digits + 2 } else { 0 };
+    let separator = if decorations.grid && decorations.numbers {
+        dimmed.paint("│ ").to_string()
+    } else {
+        String::new()
+    };
+    let rule = |joint: &str| {
+        let rule = if gutter_width > 0 {
+            format!(
+                "{}{}{}",
+                "─".repeat(gutter_width),
+                joint,
+                "─".repeat(width.saturating_sub(gutter_width + 1))
+            )
+        } else {
+            "─".repeat(width)
+        };
+        format!("{}\n", dimmed.paint(rule))
+    };
+
+    let mut out = String::new();
+    if decorations.grid {
+        out.push_str(&rule("┬"));
+    }
+    if let Some(header) = decorations.header.as_ref() {
+        out.push_str(&format!(
+            "{}{}{}\n",
+            " ".repeat(gutter_width),
+            separator,
+            AnsiStyle::new().bold().paint(header)
+        ));
+        if decorations.grid {
+            out.push_str(&rule("┼"));
+        }
+    }
+
+    let text_width = lines.iter().map(|(_, w)| *w).max().unwrap_or_default();
+    for (i, (line, line_width)) in lines.iter().enumerate() {
+        if decorations.numbers {
+            let number = format!(" {:>digits$} ", i + 1, digits = digits);
+            out.push_str(&dimmed.paint(number).to_string());
+        }
+        out.push_str(&separator);
+        out.push_str(line);
+        if let Some(pointer) = pointers.and_then(|p| p.get(i)).filter(|p| !p.is_empty()) {
+            let padding = " ".repeat(text_width - line_width + 2);
+            out.push_str(&format!("{}{}", padding, dimmed.paint(pointer)));
+        }
+        out.push('\n');
+    }
+
+    if decorations.grid {
+        out.push_str(&rule("┴"));
+    }
+    out
+}
+
+fn hex(c: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn css(style: &Style) -> String {
+    let mut css = format!("color:{};", hex(style.foreground));
+    if style.font_style.contains(FontStyle::BOLD) {
+        css.push_str("font-weight:bold;");
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        css.push_str("font-style:italic;");
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        css.push_str("text-decoration:underline;");
+    }
+    css
+}
+
+/// How an [`HtmlPrinter`] writes the colors.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HtmlStyle {
+    /// `style` attributes on each span.
+    Inline,
+    /// A stylesheet with a class for each distinct style.
+    Classes,
+}
+
+/// Writes a standalone HTML document of the highlighted text.
+pub struct HtmlPrinter<'a> {
+    assets: &'a HighlightAssets,
+    theme_name: Option<&'a str>,
+    style: HtmlStyle,
+}
+
+impl<'a> HtmlPrinter<'a> {
+    pub fn new(assets: &'a HighlightAssets, theme_name: Option<&'a str>, style: HtmlStyle) -> Self {
+        HtmlPrinter {
+            assets,
+            theme_name,
+            style,
+        }
+    }
+}
+
+impl<'a> Printer for HtmlPrinter<'a> {
+    fn print(&self, dest: &mut dyn Write, text: &FormattedText) -> Result<(), errors::Error> {
+        if text.format.is_binary() {
+            return PlainTextPrinter::default().print(dest, text);
+        }
+
+        let s = text.text();
+        let (ranges, theme) = highlight(self.assets, self.theme_name, text, &s)?;
+        Ok(write!(
+            dest,
+            "{}",
+            html_document(&ranges, theme, self.style)
+        )?)
+    }
+}
+
+fn html_document(ranges: &[(Style, &str)], theme: &Theme, style: HtmlStyle) -> String {
+    let mut pre_css = String::new();
+    if let Some(c) = theme.settings.background {
+        pre_css.push_str(&format!("background-color:{};", hex(c)));
+    }
+    if let Some(c) = theme.settings.foreground {
+        pre_css.push_str(&format!("color:{};", hex(c)));
+    }
+    pre_css.push_str("padding:1em;");
+
+    let mut classes = Vec::<String>::new();
+    let mut body = String::new();
+    for (s, text) in ranges {
+        let css = css(s);
+        let attribute = match style {
+            HtmlStyle::Inline => format!("style=\"{}\"", css),
+            HtmlStyle::Classes => {
+                let i = classes.iter().position(|c| c == &css).unwrap_or_else(|| {
+                    classes.push(css);
+                    classes.len() - 1
+                });
+                format!("class=\"refmt-{}\"", i)
+            }
+        };
+        body.push_str(&format!("<span {}>{}</span>", attribute, escape_xml(text)));
+    }
+
+    let mut head = String::from("<meta charset=\"utf-8\">\n");
+    let pre = match style {
+        HtmlStyle::Inline => format!("<pre style=\"{}\">", pre_css),
+        HtmlStyle::Classes => {
+            head.push_str("<style>\n");
+            head.push_str(&format!("pre.refmt {{ {} }}\n", pre_css));
+            for (i, css) in classes.iter().enumerate() {
+                head.push_str(&format!(".refmt-{} {{ {} }}\n", i, css));
+            }
+            head.push_str("</style>\n");
+            String::from("<pre class=\"refmt\">")
+        }
+    };
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n{}</head>\n<body>\n{}{}</pre>\n</body>\n</html>\n",
+        head, pre, body
+    )
+}
+
+/// Writes an SVG image of the highlighted text, which looks like a terminal snapshot.
+pub struct SvgPrinter<'a> {
+    assets: &'a HighlightAssets,
+    theme_name: Option<&'a str>,
+}
+
+impl<'a> SvgPrinter<'a> {
+    pub fn new(assets: &'a HighlightAssets, theme_name: Option<&'a str>) -> Self {
+        SvgPrinter { assets, theme_name }
+    }
+}
+
+impl<'a> Printer for SvgPrinter<'a> {
+    fn print(&self, dest: &mut dyn Write, text: &FormattedText) -> Result<(), errors::Error> {
+        if text.format.is_binary() {
+            return PlainTextPrinter::default().print(dest, text);
+        }
+
+        let s = text.text();
+        let (ranges, theme) = highlight(self.assets, self.theme_name, text, &s)?;
+        Ok(write!(dest, "{}", svg_document(&ranges, theme))?)
+    }
+}
+
+const SVG_FONT_SIZE: usize = 14;
+const SVG_CHAR_WIDTH: f64 = 8.4;
+const SVG_LINE_HEIGHT: usize = 20;
+const SVG_PADDING: usize = 16;
+
+fn svg_document(ranges: &[(Style, &str)], theme: &Theme) -> String {
+    // split the ranges into lines, since SVG text doesn't wrap at newlines.
+    let lines = split_lines(ranges);
+
+    let columns = lines
+        .iter()
+        .map(|l| l.iter().map(|(_, t)| t.chars().count()).sum::<usize>())
+        .max()
+        .unwrap_or_default();
+    let width = (columns as f64 * SVG_CHAR_WIDTH).ceil() as usize + SVG_PADDING * 2;
+    let height = lines.len() * SVG_LINE_HEIGHT + SVG_PADDING * 2;
+    let background = theme
+        .settings
+        .background
+        .map(hex)
+        .unwrap_or_else(|| "#000000".to_string());
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    svg.push_str(&format!(
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        background
+    ));
+    svg.push_str(&format!(
+        "<g font-family=\"monospace\" font-size=\"{}\" xml:space=\"preserve\">\n",
+        SVG_FONT_SIZE
+    ));
+    for (i, line) in lines.iter().enumerate() {
+        let y = SVG_PADDING + (i + 1) * SVG_LINE_HEIGHT - (SVG_LINE_HEIGHT - SVG_FONT_SIZE);
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\">", SVG_PADDING, y));
+        for (style, text) in line.iter().filter(|(_, t)| !t.is_empty()) {
+            let mut attributes = format!("fill=\"{}\"", hex(style.foreground));
+            if style.font_style.contains(FontStyle::BOLD) {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            if style.font_style.contains(FontStyle::ITALIC) {
+                attributes.push_str(" font-style=\"italic\"");
+            }
+            svg.push_str(&format!(
+                "<tspan {}>{}</tspan>",
+                attributes,
+                escape_xml(text)
+            ));
+        }
+        svg.push_str("</text>\n");
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use syntect::highlighting::{Color, FontStyle, Style, Theme, ThemeSettings};
+
+    use super::{
+        as_quantized_terminal_escaped, decorate, html_document, svg_document, ColorDepth,
+        Decorations, HtmlStyle,
+    };
+
+    fn style(r: u8, g: u8, b: u8) -> Style {
+        Style {
+            foreground: Color { r, g, b, a: 0xff },
+            ..Style::default()
+        }
+    }
+
+    #[test]
+    fn quantize_colors() {
+        let ranges = [
+            (style(0xf9, 0x26, 0x72), "key"),
+            (style(0xe6, 0xdb, 0x74), ": v"),
+        ];
+        assert_eq!(
+            "\x1b[38;5;203mkey\x1b[38;5;186m: v\x1b[0m",
+            as_quantized_terminal_escaped(&ranges, ColorDepth::Ansi256)
+        );
+        assert_eq!(
+            "\x1b[35mkey\x1b[37m: v\x1b[0m",
+            as_quantized_terminal_escaped(&ranges, ColorDepth::Ansi16)
+        );
+    }
+
+    #[test]
+    fn color_depth_names() {
+        for name in ColorDepth::names() {
+            assert!(ColorDepth::from_name(name).is_some());
+        }
+        assert_eq!(Some(ColorDepth::TrueColor), ColorDepth::from_name("24bit"));
+        assert_eq!(None, ColorDepth::from_name("8"));
+    }
+
+    #[test]
+    fn color_depth_from_terms() {
+        let cases = [
+            ("truecolor", "xterm-256color", ColorDepth::TrueColor),
+            ("24bit", "linux", ColorDepth::TrueColor),
+            ("", "xterm-256color", ColorDepth::Ansi256),
+            ("", "screen-256color", ColorDepth::Ansi256),
+            ("", "tmux-256color", ColorDepth::Ansi256),
+            ("yes", "xterm-256color", ColorDepth::Ansi256),
+            ("", "linux", ColorDepth::Ansi16),
+            ("", "xterm-16color", ColorDepth::Ansi16),
+            ("", "xterm", ColorDepth::TrueColor),
+            ("", "", ColorDepth::TrueColor),
+        ];
+        for (colorterm, term, depth) in cases {
+            assert_eq!(
+                depth,
+                ColorDepth::from_terms(colorterm, term),
+                "{} {}",
+                colorterm,
+                term
+            );
+        }
+    }
+
+    fn theme() -> Theme {
+        Theme {
+            settings: ThemeSettings {
+                background: Some(Color {
+                    r: 0x27,
+                    g: 0x28,
+                    b: 0x22,
+                    a: 0xff,
+                }),
+                ..ThemeSettings::default()
+            },
+            ..Theme::default()
+        }
+    }
+
+    #[test]
+    fn html() {
+        let mut bold = style(0xf9, 0x26, 0x72);
+        bold.font_style = FontStyle::BOLD;
+        let ranges = [
+            (bold, "<key>"),
+            (style(0xf8, 0xf8, 0xf2), ": "),
+            (bold, "v\n"),
+        ];
+
+        let html = html_document(&ranges, &theme(), HtmlStyle::Inline);
+        assert!(html.contains(concat!(
+            r#"<pre style="background-color:#272822;padding:1em;">"#,
+            r#"<span style="color:#f92672;font-weight:bold;">&lt;key&gt;</span>"#,
+            r#"<span style="color:#f8f8f2;">: </span>"#,
+            r#"<span style="color:#f92672;font-weight:bold;">v"#,
+        )));
+
+        let html = html_document(&ranges, &theme(), HtmlStyle::Classes);
+        assert!(html.contains(".refmt-0 { color:#f92672;font-weight:bold; }\n"));
+        assert!(html.contains(".refmt-1 { color:#f8f8f2; }\n"));
+        assert!(html.contains(concat!(
+            r#"<pre class="refmt"><span class="refmt-0">&lt;key&gt;</span>"#,
+            r#"<span class="refmt-1">: </span><span class="refmt-0">v"#,
+        )));
+    }
+
+    #[test]
+    fn svg() {
+        let ranges = [
+            (style(0xf9, 0x26, 0x72), "a: 1\nbc: "),
+            (style(0xe6, 0xdb, 0x74), "2\n"),
+        ];
+        let svg = svg_document(&ranges, &theme());
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="74" height="72""#)
+        );
+        assert!(svg.contains(r##"<rect width="100%" height="100%" fill="#272822"/>"##));
+        assert!(svg.contains(r##"<text x="16" y="30"><tspan fill="#f92672">a: 1</tspan></text>"##));
+        assert!(svg.contains(concat!(
+            r##"<text x="16" y="50"><tspan fill="#f92672">bc: </tspan>"##,
+            r##"<tspan fill="#e6db74">2</tspan></text>"##,
+        )));
+    }
+
+    #[test]
+    fn decorations() {
+        let lines = [("a: 1".to_string(), 4), ("bc: 2".to_string(), 5)];
+        let pointers = ["/a".to_string(), "/bc".to_string()];
+        let decorations = Decorations::from_names(["full", "pointers"], "config.toml → yaml");
+        let out = decorate(&lines, Some(&pointers), &decorations, 12);
+        let plain = out
+            .split("\x1b[")
+            .map(|s| s.trim_start_matches(|c: char| c.is_ascii_digit() || c == ';'))
+            .map(|s| s.strip_prefix('m').unwrap_or(s))
+            .collect::<String>();
+        assert_eq!(
+            concat!(
+                "─────┬──────\n",
+                "     │ config.toml → yaml\n",
+                "─────┼──────\n",
+                "   1 │ a: 1   /a\n",
+                "   2 │ bc: 2  /bc\n",
+                "─────┴──────\n",
+            ),
+            plain
+        );
+
+        let decorations = Decorations::from_names(["numbers"], "");
+        assert_eq!(
+            "\x1b[2m   1 \x1b[0ma: 1\n\x1b[2m   2 \x1b[0mbc: 2\n",
+            decorate(&lines, None, &decorations, 12)
+        );
+    }
+}
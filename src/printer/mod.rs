@@ -0,0 +1,25 @@
+use std::io::Write;
+
+use crate::errors;
+use crate::format::FormattedText;
+
+#[cfg(feature = "highlight")]
+mod highlight;
+
+#[cfg(feature = "highlight")]
+pub use self::highlight::{
+    ColorDepth, Decorations, HighlightTextPrinter, HtmlPrinter, HtmlStyle, SvgPrinter,
+};
+
+pub trait Printer {
+    fn print(&self, dest: &mut dyn Write, text: &FormattedText) -> Result<(), errors::Error>;
+}
+
+#[derive(Default)]
+pub struct PlainTextPrinter {}
+
+impl Printer for PlainTextPrinter {
+    fn print(&self, dest: &mut dyn Write, text: &FormattedText) -> Result<(), errors::Error> {
+        dest.write_all(&text.bytes).map_err(errors::Error::Io)
+    }
+}
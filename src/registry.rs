@@ -0,0 +1,492 @@
+//! A registry of the formats by their names and extensions, which has the bundled formats and
+//! the formats added by other crates.
+//!
+//! A [`FormatPlugin`] is registered with [`register`], and is available as
+//! [`FileFormat::Plugin`] by its name and extensions, like the bundled formats. So the command
+//! line options and [`Converter`](crate::converter::Converter) accept it without changes.
+//!
+//! ```
+//! use refmt::converter::Converter;
+//! use refmt::format::FileFormat;
+//! use refmt::registry::{self, FormatPlugin, PluginError};
+//!
+//! /// `key=value` lines.
+//! struct Properties;
+//!
+//! impl FormatPlugin for Properties {
+//!     fn name(&self) -> &'static str {
+//!         "properties"
+//!     }
+//!
+//!     fn extensions(&self) -> &[&'static str] {
+//!         &["properties"]
+//!     }
+//!
+//!     fn deserialize(&self, b: &[u8]) -> Result<serde_json::Value, PluginError> {
+//!         let map = std::str::from_utf8(b)?
+//!             .lines()
+//!             .filter_map(|line| line.split_once('='))
+//!             .map(|(k, v)| (k.to_string(), v.into()))
+//!             .collect();
+//!         Ok(serde_json::Value::Object(map))
+//!     }
+//!
+//!     fn serialize(&self, v: &serde_json::Value) -> Result<Vec<u8>, PluginError> {
+//!         let map = v.as_object().ok_or("properties requires an object")?;
+//!         Ok(map
+//!             .iter()
+//!             .map(|(k, v)| format!("{}={}\n", k, v.as_str().unwrap_or_default()))
+//!             .collect::<String>()
+//!             .into_bytes())
+//!     }
+//! }
+//!
+//! let properties = registry::register(Properties)?;
+//! let mut out = Vec::new();
+//! Converter::new()
+//!     .from(properties)
+//!     .to(FileFormat::Yaml)
+//!     .convert("name=refmt\n".as_bytes(), &mut out)?;
+//! assert_eq!("name: refmt\n", String::from_utf8(out).unwrap());
+//! # Ok::<(), refmt::errors::Error>(())
+//! ```
+
+use std::sync::{LazyLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use refmt_serde::{Format, RefmtError};
+
+use crate::errors;
+use crate::format::{FileFormat, FormattedText, Transforms};
+
+pub type PluginError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A format which is converted from and into a JSON value.
+pub trait FormatPlugin: Send + Sync {
+    /// The name of the format, for `--input-format` and `--output-format`.
+    fn name(&self) -> &'static str;
+
+    /// The extensions of the files, which may include the name.
+    fn extensions(&self) -> &[&'static str];
+
+    /// The extension of the syntax for highlighting, or plain text if the syntax is not bundled.
+    fn syntax_extension(&self) -> &'static str {
+        "txt"
+    }
+
+    fn is_binary(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the format can be an output, but not an input.
+    fn is_write_only(&self) -> bool {
+        false
+    }
+
+    fn deserialize(&self, b: &[u8]) -> Result<serde_json::Value, PluginError>;
+    fn serialize(&self, v: &serde_json::Value) -> Result<Vec<u8>, PluginError>;
+}
+
+/// How the values of a format are read and written.
+#[derive(Copy, Clone)]
+pub enum Codec {
+    /// A format of refmt-serde, which is converted into other bundled formats directly.
+    Bundled(Format),
+    /// A plugin, which is converted through JSON values.
+    Plugin(&'static dyn FormatPlugin),
+}
+
+/// A format of the registry, which is bundled or registered by a plugin.
+#[derive(Copy, Clone)]
+pub struct Entry {
+    format: FileFormat,
+    extensions: &'static [&'static str],
+    syntax_extension: &'static str,
+    binary: bool,
+    write_only: bool,
+    codec: Codec,
+}
+
+impl Entry {
+    const fn bundled(
+        format: FileFormat,
+        codec: Format,
+        extensions: &'static [&'static str],
+        syntax_extension: &'static str,
+    ) -> Entry {
+        Entry {
+            format,
+            extensions,
+            syntax_extension,
+            binary: false,
+            write_only: false,
+            codec: Codec::Bundled(codec),
+        }
+    }
+
+    const fn binary(mut self) -> Entry {
+        self.binary = true;
+        self
+    }
+
+    const fn write_only(mut self) -> Entry {
+        self.write_only = true;
+        self
+    }
+
+    fn plugin(plugin: &'static dyn FormatPlugin) -> Entry {
+        Entry {
+            format: FileFormat::Plugin(plugin.name()),
+            extensions: plugin.extensions(),
+            syntax_extension: plugin.syntax_extension(),
+            binary: plugin.is_binary(),
+            write_only: plugin.is_write_only(),
+            codec: Codec::Plugin(plugin),
+        }
+    }
+
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.format {
+            FileFormat::Plugin(name) => name,
+            _ => self.extensions[0],
+        }
+    }
+
+    pub fn extensions(&self) -> &'static [&'static str] {
+        self.extensions
+    }
+
+    pub fn syntax_extension(&self) -> &'static str {
+        self.syntax_extension
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    pub fn is_write_only(&self) -> bool {
+        self.write_only
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Returns true if the name or one of the extensions is the name.
+    fn is_named(&self, name: &str) -> bool {
+        self.name() == name || self.extensions.contains(&name)
+    }
+}
+
+/// The bundled formats. The first extension is the name of the format.
+///
+/// The tables are selected only by their names, since `.md` and `.html` files are not tables
+/// to read, and highlighted HTML documents are written by `--export html`.
+const BUNDLED: &[Entry] = &[
+    Entry::bundled(FileFormat::Bson, Format::Bson, &["bson"], "bson").binary(),
+    Entry::bundled(FileFormat::Edn, Format::Edn, &["edn"], "clj"),
+    // no syntax is bundled for HCL and KDL.
+    Entry::bundled(
+        FileFormat::Hcl,
+        Format::Hcl,
+        &["hcl", "tf", "tfvars"],
+        "txt",
+    ),
+    Entry::bundled(FileFormat::Json, Format::Json, &["json"], "json"),
+    Entry::bundled(FileFormat::Json5, Format::Json5, &["json5"], "json"),
+    Entry::bundled(FileFormat::Jsonc, Format::Jsonc, &["jsonc"], "json"),
+    Entry::bundled(FileFormat::Kdl, Format::Kdl, &["kdl"], "txt"),
+    Entry::bundled(FileFormat::Plist, Format::Plist, &["plist"], "xml"),
+    Entry::bundled(
+        FileFormat::BinaryPlist,
+        Format::BinaryPlist,
+        &["bplist"],
+        "bplist",
+    )
+    .binary(),
+    Entry::bundled(FileFormat::Ron, Format::Ron, &["ron"], "rs"),
+    Entry::bundled(FileFormat::Toml, Format::Toml, &["toml"], "toml"),
+    Entry::bundled(FileFormat::Yaml, Format::Yaml, &["yaml", "yml"], "yaml"),
+    Entry::bundled(
+        FileFormat::MarkdownTable,
+        Format::MarkdownTable,
+        &["markdown-table"],
+        "md",
+    )
+    .write_only(),
+    Entry::bundled(
+        FileFormat::HtmlTable,
+        Format::HtmlTable,
+        &["html-table"],
+        "html",
+    )
+    .write_only(),
+];
+
+/// The formats by their names and extensions. The global registry is used by [`FileFormat`],
+/// and other registries are for looking up formats without registering them globally.
+#[derive(Clone)]
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::bundled()
+    }
+}
+
+impl Registry {
+    /// Returns the registry of the bundled formats.
+    pub fn bundled() -> Registry {
+        Registry {
+            entries: BUNDLED.to_vec(),
+        }
+    }
+
+    /// Registers the format, and returns the format to convert with.
+    ///
+    /// The name and the extensions must not be taken by other formats.
+    pub fn register<P: FormatPlugin + 'static>(
+        &mut self,
+        plugin: P,
+    ) -> Result<FileFormat, errors::Error> {
+        let mut names = [plugin.name()]
+            .into_iter()
+            .chain(plugin.extensions().iter().copied());
+        if let Some(name) = names.find(|name| self.find(name).is_some()) {
+            return Err(errors::Error::DuplicateFormat(name.to_string()));
+        }
+
+        let entry = Entry::plugin(Box::leak(Box::new(plugin)));
+        self.entries.push(entry);
+        Ok(entry.format)
+    }
+
+    /// Returns the format of the name or the extension.
+    pub fn find(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.is_named(name))
+    }
+
+    pub fn get(&self, format: FileFormat) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.format == format)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(|| RwLock::new(Registry::bundled()));
+
+fn global() -> RwLockReadGuard<'static, Registry> {
+    REGISTRY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn global_mut() -> RwLockWriteGuard<'static, Registry> {
+    REGISTRY.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Registers the format globally, and returns the format to convert with.
+///
+/// The name and the extensions must not be taken by the bundled formats or other plugins.
+pub fn register<P: FormatPlugin + 'static>(plugin: P) -> Result<FileFormat, errors::Error> {
+    global_mut().register(plugin)
+}
+
+/// Returns the registered format of the name or the extension.
+pub fn find(name: &str) -> Option<Entry> {
+    global().find(name).copied()
+}
+
+/// Returns the registered format.
+pub fn get(format: FileFormat) -> Option<Entry> {
+    global().get(format).copied()
+}
+
+/// Returns all registered formats, the bundled formats first.
+pub fn entries() -> Vec<Entry> {
+    global().entries().to_vec()
+}
+
+/// Returns the registered plugin of the name.
+pub fn plugin(name: &str) -> Option<&'static dyn FormatPlugin> {
+    plugins().into_iter().find(|p| p.name() == name)
+}
+
+/// Returns all registered plugins.
+pub fn plugins() -> Vec<&'static dyn FormatPlugin> {
+    global()
+        .entries()
+        .iter()
+        .filter_map(|e| match e.codec {
+            Codec::Plugin(p) => Some(p),
+            Codec::Bundled(_) => None,
+        })
+        .collect()
+}
+
+/// Converts the text through a JSON value, since plugins read and write JSON values. The JSON
+/// text is transformed, so that the values of plugins are also transformed.
+pub(crate) fn convert(
+    text: &FormattedText,
+    to: FileFormat,
+    transforms: &Transforms,
+) -> Result<FormattedText, errors::Error> {
+    let json = match text.format {
+        FileFormat::Plugin(name) => {
+            let value = codec(name)?
+                .deserialize(&text.bytes)
+                .map_err(|cause| errors::Error::Plugin { name, cause })?;
+            let json = serde_json::to_vec(&value).map_err(RefmtError::Json)?;
+            FormattedText::from_bytes(FileFormat::Json, json)
+                .convert_to_with(FileFormat::Json, transforms)?
+        }
+        _ => text.convert_to_with(FileFormat::Json, transforms)?,
+    };
+
+    match to {
+        FileFormat::Plugin(name) => {
+            let value = serde_json::from_slice(&json.bytes).map_err(RefmtError::Json)?;
+            let bytes = codec(name)?
+                .serialize(&value)
+                .map_err(|cause| errors::Error::Plugin { name, cause })?;
+            Ok(FormattedText::from_bytes(to, bytes))
+        }
+        to => json.convert_to(to),
+    }
+}
+
+fn codec(name: &'static str) -> Result<&'static dyn FormatPlugin, errors::Error> {
+    plugin(name).ok_or_else(|| errors::Error::FormatName(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{register, Codec, FormatPlugin, PluginError, Registry};
+    use crate::errors;
+    use crate::format::{FileFormat, FormattedText};
+
+    /// Lines of strings, which is an array.
+    struct Lines;
+
+    impl FormatPlugin for Lines {
+        fn name(&self) -> &'static str {
+            "lines"
+        }
+
+        fn extensions(&self) -> &[&'static str] {
+            &["lines", "lst"]
+        }
+
+        fn deserialize(&self, b: &[u8]) -> Result<serde_json::Value, PluginError> {
+            Ok(std::str::from_utf8(b)?.lines().collect())
+        }
+
+        fn serialize(&self, v: &serde_json::Value) -> Result<Vec<u8>, PluginError> {
+            let lines = v.as_array().ok_or("lines requires an array")?;
+            Ok(lines
+                .iter()
+                .map(|v| format!("{}\n", v.as_str().unwrap_or_default()))
+                .collect::<String>()
+                .into_bytes())
+        }
+    }
+
+    /// JSON as it is, whose name is taken by the bundled format.
+    struct Json(&'static str);
+
+    impl FormatPlugin for Json {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn extensions(&self) -> &[&'static str] {
+            &[]
+        }
+
+        fn deserialize(&self, b: &[u8]) -> Result<serde_json::Value, PluginError> {
+            Ok(serde_json::from_slice(b)?)
+        }
+
+        fn serialize(&self, v: &serde_json::Value) -> Result<Vec<u8>, PluginError> {
+            Ok(serde_json::to_vec(v)?)
+        }
+    }
+
+    #[test]
+    fn bundled() {
+        let registry = Registry::bundled();
+        let yaml = registry.find("yml").unwrap();
+        assert_eq!(FileFormat::Yaml, yaml.format());
+        assert_eq!("yaml", yaml.name());
+        assert_eq!(
+            Some("yaml"),
+            registry.get(FileFormat::Yaml).map(|e| e.name())
+        );
+        assert!(registry.find("bplist").unwrap().is_binary());
+        assert!(registry.find("conf").is_none());
+    }
+
+    #[test]
+    fn register_names() -> Result<(), errors::Error> {
+        // a registry of the test, so that the plugins are not seen by other tests.
+        let mut registry = Registry::bundled();
+        let raw = registry.register(Json("raw-json"))?;
+        assert_eq!(FileFormat::Plugin("raw-json"), raw);
+        match registry.get(raw).map(|e| e.codec()) {
+            Some(Codec::Plugin(p)) => {
+                let v = p.deserialize(b"[1]").unwrap();
+                assert_eq!(b"[1]".to_vec(), p.serialize(&v).unwrap());
+            }
+            _ => panic!("raw-json is not a plugin"),
+        }
+
+        // the names are taken.
+        assert!(matches!(
+            registry.register(Json("raw-json")),
+            Err(errors::Error::DuplicateFormat(_))
+        ));
+        assert!(matches!(
+            registry.register(Json("json")),
+            Err(errors::Error::DuplicateFormat(_))
+        ));
+        assert!(matches!(
+            registry.register(Json("yml")),
+            Err(errors::Error::DuplicateFormat(_))
+        ));
+        Ok(())
+    }
+
+    /// The only test which registers globally, since the global registry can't be restored.
+    #[test]
+    fn plugin() -> Result<(), errors::Error> {
+        let lines = register(Lines)?;
+        assert_eq!(FileFormat::Plugin("lines"), lines);
+        assert_eq!(lines, FileFormat::from_str("lst")?);
+        assert!(FileFormat::names().contains(&"lines"));
+        assert!(FileFormat::readable_names().contains(&"lines"));
+
+        let yaml = FormattedText::new(lines, "a\nb\n".to_string()).convert_to(FileFormat::Yaml)?;
+        assert_eq!("- a\n- b\n", yaml.text());
+        let text = yaml.convert_to(lines)?;
+        assert_eq!("a\nb\n", text.text());
+
+        let r = FormattedText::new(FileFormat::Json, "{}".to_string()).convert_to(lines);
+        assert!(matches!(
+            r,
+            Err(errors::Error::Plugin { name: "lines", .. })
+        ));
+
+        assert!(matches!(
+            register(Lines),
+            Err(errors::Error::DuplicateFormat(_))
+        ));
+        Ok(())
+    }
+}